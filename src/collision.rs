@@ -9,6 +9,9 @@
 //!
 //!Rough detection: Collisisions that affect the object as a whole. E.g. collisions with walls, which should shift the position of the whole object back
 //!
+//!Implemented as a sweep and prune broadphase over the bounds, followed by a separating axis test on the triangles
+//!of each broadphase hit.
+//!
 //! On detected collision:
 //!  - Call "collision detected" trait method.
 //!  - Each object implements its own resolution, depending on collision details and the other object. Alternatively, the collision detector could handle resolution logic, and just update objects with e.g. new positions.
@@ -23,49 +26,505 @@ use crate::{
 
 use super::*;
 use itertools::Itertools;
+use layers::CollisionMasks;
+
+pub mod layers;
+pub mod query;
 
-pub fn collisions(states: &[StateMachineEnum]) {
-    let collision_boxes: Vec<CollisionBox> = states.iter().flat_map(StateMachineEnum::collision_box).collect();
-    let collisions = find_collisions(&collision_boxes);
+pub fn collisions(states: &[StateMachineEnum], masks: &CollisionMasks) -> Vec<Contact> {
+    find_collisions(&collision_boxes(states), masks)
+}
 
-    if DEBUG_DRAW_COLLISION_BOXES {
-        draw::debug_draw_collision_boxes(&collision_boxes, RED);
-        draw::debug_draw_collided_boxes(&collisions, GREEN);
+/// Colliders of all states, tagged with the index of the state they belong to
+pub fn collision_boxes(states: &[StateMachineEnum]) -> Vec<Collider> {
+    states
+        .iter()
+        .enumerate()
+        .flat_map(|(entity, state)| {
+            state.collision_shape().into_iter().map(move |(part, triangles)| Collider {
+                owner: Owner { entity, part },
+                bounds: triangles.bounds(),
+                triangles,
+            })
+        })
+        .collect()
+}
+
+/// Lets both parties of every contact react to it
+pub fn resolve_contacts(states: &mut [StateMachineEnum], contacts: &[Contact]) {
+    for contact in contacts {
+        for contact in [*contact, contact.swapped()] {
+            let state = std::mem::take(&mut states[contact.own.entity]);
+            states[contact.own.entity] = state.collision_detected(&contact);
+        }
     }
 }
 
 pub trait Collision: Draw {
-    fn collision_box(&self) -> Vec<CollisionBox>;
-    fn collision_detected(&self /*other object */);
-    fn bounds<const N: usize>(vertices: Vertices<N>) -> CollisionBox {
-        let (lower, upper) = Vec::<Vec2>::from(vertices.value())
+    fn collision_shape(&self) -> Vec<(Part, Triangles)>;
+    /// `contact` is seen from this state's side, i.e. `contact.own` is one of its parts
+    fn collision_detected(self, contact: &Contact) -> Self;
+    fn triangles<const N: usize>(vertices: Vertices<N>) -> Triangles {
+        vertices.into()
+    }
+}
+
+/// The part of a state machine a collider belongs to
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Part {
+    PlayerBody,
+    /// `hook` tells the hook apart from the others its player has out. Only a `catching` head, one that is still
+    /// extending, catches the items it touches
+    HookHead { hook: usize, catching: bool },
+    /// A straight piece of the chain's path, counted from the head. Corners the chain is wrapped on split a piece in
+    /// two, so `segment` is not the index of a link
+    ChainSegment { hook: usize, segment: usize },
+    Item,
+    /// An item that has been caught and is on its way to the player
+    Pickup,
+    Wall,
+    /// The head of a hook whose chain snapped
+    DroppedHook,
+}
+
+impl Part {
+    /// Which child of a [Hierarchy](crate::state::hierarchy::Hierarchy) the part belongs to, `None` for the parts of
+    /// the parent itself
+    pub fn child(&self) -> Option<usize> {
+        match self {
+            Part::HookHead { hook, .. } | Part::ChainSegment { hook, .. } => Some(*hook),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Owner {
+    /// Index of the state machine in the world
+    pub entity: usize,
+    pub part: Part,
+}
+impl Owner {
+    /// Whether both parts belong to the same child of the same state machine, e.g. the head of one hook before and
+    /// after it turned back
+    pub fn same_child(&self, other: &Owner) -> bool {
+        self.entity == other.entity && self.part.child() == other.part.child()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Collider {
+    pub owner: Owner,
+    /// Used by the broadphase
+    pub bounds: CollisionBox,
+    /// Used by the narrow phase
+    pub triangles: Triangles,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Contact {
+    pub own: Owner,
+    pub other: Owner,
+    /// Where the bounds of the two colliders overlap
+    pub overlap: CollisionBox,
+    /// How far `own` has to move along `-normal` (or `other` along `normal`) to separate the two
+    pub depth: f32,
+    /// Unit vector pointing from `own` toward `other`
+    pub normal: Vec2,
+}
+impl Contact {
+    /// The same contact, seen from the other party
+    pub fn swapped(&self) -> Contact {
+        Contact {
+            own: self.other,
+            other: self.own,
+            overlap: self.overlap,
+            depth: self.depth,
+            normal: -self.normal,
+        }
+    }
+}
+
+/// World space triangles, three vertices each, the same way `draw_vertex_graphics` draws them
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Triangles(pub Vec<[Vec2; 3]>);
+/// Triangles per round end of [Triangles::capsule]
+const CAPSULE_END_TRIANGLES: usize = 4;
+/// Pixels [sweep_triangles] moves its shape between two overlap tests
+const SWEEP_TRIANGLES_STEP: f32 = 1.0;
+impl Triangles {
+    pub fn bounds(&self) -> CollisionBox {
+        let (lower, upper) = self
+            .0
             .iter()
+            .flatten()
             .fold((Vec2::MAX, Vec2::MIN), |acc, v| (v.min(acc.0), v.max(acc.1)));
         CollisionBox { lower, upper }
     }
+    /// The segment from `a` to `b` thickened by `radius`, with round ends made of a few triangles each
+    pub fn capsule(a: Vec2, b: Vec2, radius: f32) -> Self {
+        let axis = (b - a).normalize_or(Vec2::X);
+        let side = axis.perp() * radius;
+        let mut triangles = vec![[a - side, b - side, b + side], [a - side, b + side, a + side]];
+        for (end, outwards) in [(b, axis), (a, -axis)] {
+            let rim = |i: usize| {
+                let angle = std::f32::consts::PI * (i as f32 / CAPSULE_END_TRIANGLES as f32 - 0.5);
+                end + outwards.rotate(Vec2::from_angle(angle)) * radius
+            };
+            triangles.extend((0..CAPSULE_END_TRIANGLES).map(|i| [end, rim(i), rim(i + 1)]));
+        }
+        Triangles(triangles)
+    }
+    pub fn translate(&self, offset: Vec2) -> Self {
+        Triangles(self.0.iter().map(|t| t.map(|v| v + offset)).collect())
+    }
+    /// Whether any of the triangles overlaps any of `other`'s
+    pub fn overlaps(&self, other: &Triangles) -> bool {
+        self.0.iter().cartesian_product(&other.0).any(|(s, t)| penetration(s, t).is_some())
+    }
+}
+impl<const N: usize> From<Vertices<N>> for Triangles {
+    fn from(vertices: Vertices<N>) -> Self {
+        Triangles(vertices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect())
+    }
+}
+impl From<CollisionBox> for Triangles {
+    fn from(CollisionBox { lower, upper }: CollisionBox) -> Self {
+        let (upper_left, lower_right) = (Vec2::new(lower.x, upper.y), Vec2::new(upper.x, lower.y));
+        Triangles(vec![[lower, lower_right, upper], [lower, upper, upper_left]])
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct CollisionBox {
     pub lower: Vec2,
     pub upper: Vec2,
 }
 impl CollisionBox {
-    fn collision_with(&self, other: &CollisionBox) -> bool {
+    pub fn collision_with(&self, other: &CollisionBox) -> bool {
         is_overlapping((self.lower.x, self.upper.x), (other.lower.x, other.upper.x))
             && is_overlapping((self.lower.y, self.upper.y), (other.lower.y, other.upper.y))
     }
+    pub fn overlap(&self, other: &CollisionBox) -> Option<CollisionBox> {
+        self.collision_with(other).then(|| CollisionBox {
+            lower: self.lower.max(other.lower),
+            upper: self.upper.min(other.upper),
+        })
+    }
+}
+
+/// Casts `moving` along `motion` and returns the earliest time of impact with any of `targets`, as a fraction of
+/// `motion` between 0 and 1. Targets that `moving` already overlaps are hit at 0, so whatever stopped just inside a
+/// target cannot pass through it on the next step
+pub fn sweep<'a>(
+    moving: &CollisionBox,
+    motion: Vec2,
+    targets: impl IntoIterator<Item = &'a CollisionBox>,
+) -> Option<f32> {
+    targets
+        .into_iter()
+        .filter_map(|target| {
+            if target.collision_with(moving) {
+                return Some(0.0);
+            }
+            let (entry, exit) = sweep_window(moving, motion, target)?;
+            (entry <= exit && (0.0..=1.0).contains(&entry)).then_some(entry)
+        })
+        .min_by(f32::total_cmp)
+}
+
+/// Like [sweep], but a target only counts as hit once the triangles of `moving` overlap its triangles. Where the
+/// bounds overlap along `motion`, `moving` is moved in steps of at most [SWEEP_TRIANGLES_STEP] pixels and tested at
+/// each of them, so it can pass the empty corners of a target's bounds
+pub fn sweep_triangles<'a>(
+    moving: &Triangles,
+    motion: Vec2,
+    targets: impl IntoIterator<Item = &'a Triangles>,
+) -> Option<f32> {
+    let bounds = moving.bounds();
+    let steps = (motion.length() / SWEEP_TRIANGLES_STEP).ceil().max(1.0);
+    targets
+        .into_iter()
+        .filter_map(|target| {
+            let (entry, exit) = sweep_window(&bounds, motion, &target.bounds())?;
+            if entry > exit || entry > 1.0 || exit < 0.0 {
+                return None;
+            }
+            let (first, last) = ((entry.max(0.0) * steps).floor() as usize, (exit.min(1.0) * steps).ceil() as usize);
+            (first..=last)
+                .map(|step| step as f32 / steps)
+                .find(|time| moving.translate(motion * *time).overlaps(target))
+        })
+        .min_by(f32::total_cmp)
+}
+
+/// When the bounds of `moving` enter and leave `target` along `motion`, as fractions of `motion`. Either may lie
+/// outside of 0 to 1. `None` if they never meet
+fn sweep_window(moving: &CollisionBox, motion: Vec2, target: &CollisionBox) -> Option<(f32, f32)> {
+    let half_size = (moving.upper - moving.lower) / 2.0;
+    let origin = moving.lower + half_size;
+    // Ray from the center of `moving` against the target grown by the size of `moving`
+    let (lower, upper) = (target.lower - half_size, target.upper + half_size);
+    let mut entry = f32::MIN;
+    let mut exit = f32::MAX;
+    for axis in 0..2 {
+        if motion[axis] == 0.0 {
+            if origin[axis] <= lower[axis] || origin[axis] >= upper[axis] {
+                return None;
+            }
+        } else {
+            let t1 = (lower[axis] - origin[axis]) / motion[axis];
+            let t2 = (upper[axis] - origin[axis]) / motion[axis];
+            entry = entry.max(t1.min(t2));
+            exit = exit.min(t1.max(t2));
+        }
+    }
+    Some((entry, exit))
+}
+
+/// Sweep and prune along the x axis: only colliders whose x extents overlap are tested against each other.
+/// Contacts come out in the same order as from checking every pair
+fn find_collisions(colliders: &[Collider], masks: &CollisionMasks) -> Vec<Contact> {
+    let mut order: Vec<usize> = (0..colliders.len()).collect();
+    order.sort_by(|a, b| colliders[*a].bounds.lower.x.total_cmp(&colliders[*b].bounds.lower.x));
+
+    let mut active: Vec<usize> = vec![];
+    let mut pairs = vec![];
+    for i in order {
+        let bounds = &colliders[i].bounds;
+        active.retain(|a| colliders[*a].bounds.upper.x > bounds.lower.x);
+        pairs.extend(
+            active
+                .iter()
+                .filter(|a| may_collide(&colliders[**a], &colliders[i], masks))
+                .filter(|a| colliders[**a].bounds.collision_with(bounds))
+                .map(|a| (i.min(*a), i.max(*a))),
+        );
+        active.push(i);
+    }
+    pairs.sort_unstable();
+    pairs
+        .into_iter()
+        .filter_map(|(a, b)| contact(&colliders[a], &colliders[b]))
+        .collect()
 }
 
-fn find_collisions(boxes: &[CollisionBox]) -> Vec<(CollisionBox, CollisionBox)> {
-    boxes
+#[cfg(test)]
+fn find_collisions_brute_force(colliders: &[Collider], masks: &CollisionMasks) -> Vec<Contact> {
+    colliders
         .iter()
         .combinations(2)
-        .filter(|p| p[0].collision_with(p[1]))
-        .map(|p| (p[0].to_owned(), p[1].to_owned()))
+        .filter(|p| may_collide(p[0], p[1], masks))
+        .filter_map(|p| contact(p[0], p[1]))
         .collect()
 }
 
+/// Parts of the same state machine never collide with each other, e.g. the player body and its own hook head
+fn may_collide(a: &Collider, b: &Collider, masks: &CollisionMasks) -> bool {
+    a.owner.entity != b.owner.entity && masks.collides(a.owner.part.layer(), b.owner.part.layer())
+}
+
+/// Narrow phase. Reports the deepest penetration among the overlapping triangle pairs
+fn contact(a: &Collider, b: &Collider) -> Option<Contact> {
+    let overlap = a.bounds.overlap(&b.bounds)?;
+    let (depth, normal) = a
+        .triangles
+        .0
+        .iter()
+        .cartesian_product(&b.triangles.0)
+        .filter_map(|(s, t)| penetration(s, t))
+        .max_by(|p, q| p.0.total_cmp(&q.0))?;
+    Some(Contact {
+        own: a.owner,
+        other: b.owner,
+        overlap,
+        depth,
+        normal,
+    })
+}
+
+/// Separating axis test between two triangles. Returns the penetration depth and the axis of least penetration,
+/// pointing from `a` toward `b`, or `None` if some axis separates them
+fn penetration(a: &[Vec2; 3], b: &[Vec2; 3]) -> Option<(f32, Vec2)> {
+    let edge_normals =
+        |t: &[Vec2; 3]| [t[1] - t[0], t[2] - t[1], t[0] - t[2]].map(|edge| edge.perp().normalize_or_zero());
+    let project = |t: &[Vec2; 3], axis: Vec2| {
+        t.iter()
+            .map(|v| v.dot(axis))
+            .fold((f32::MAX, f32::MIN), |(min, max), p| (min.min(p), max.max(p)))
+    };
+
+    let mut least: Option<(f32, Vec2)> = None;
+    for axis in edge_normals(a).into_iter().chain(edge_normals(b)) {
+        if axis == Vec2::ZERO {
+            continue;
+        }
+        let ((a_min, a_max), (b_min, b_max)) = (project(a, axis), project(b, axis));
+        let depth = a_max.min(b_max) - a_min.max(b_min);
+        if depth <= 0.0 {
+            return None;
+        }
+        if least.is_none_or(|(least_depth, _)| depth < least_depth) {
+            least = Some((depth, axis));
+        }
+    }
+    let center = |t: &[Vec2; 3]| (t[0] + t[1] + t[2]) / 3.0;
+    least.map(|(depth, axis)| {
+        let towards_b = if axis.dot(center(b) - center(a)) < 0.0 { -axis } else { axis };
+        (depth, towards_b)
+    })
+}
+
 fn is_overlapping(p1: (f32, f32), p2: (f32, f32)) -> bool {
     p1.1 > p2.0 && p2.1 > p1.0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::hook::HOOK_SPEC;
+    use crate::state::item::ItemStateMachine;
+    use crate::state::player::{PLAYER_PHYSICS, Player};
+    use crate::state::state_machine::item::build;
+    use crate::state::wall::Wall;
+
+    #[test]
+    fn contacts_identify_both_parties() {
+        let position = Position::new(200.0, 200.0);
+        let states = [
+            StateMachineEnum::Player(Player::new(position, RIGHT, PLAYER_PHYSICS, HOOK_SPEC)),
+            StateMachineEnum::Wall(Wall::new(Position::new(600.0, 200.0), 40.0, 40.0)),
+            StateMachineEnum::Item(ItemStateMachine::Moving(build(position, RIGHT, Magnitude::zero()))),
+        ];
+
+        let contacts = collisions(&states, &CollisionMasks::default());
+
+        assert_eq!(contacts.len(), 1);
+        let contact = contacts[0];
+        assert_eq!(contact.own, Owner { entity: 0, part: Part::PlayerBody });
+        assert_eq!(contact.other, Owner { entity: 2, part: Part::Item });
+        assert!(contact.overlap.lower.cmple(position.value()).all());
+        assert!(contact.overlap.upper.cmpge(position.value()).all());
+        assert_eq!(contact.swapped().own, contact.other);
+    }
+
+    fn collider(entity: usize, triangles: Triangles) -> Collider {
+        Collider {
+            owner: Owner { entity, part: Part::Item },
+            bounds: triangles.bounds(),
+            triangles,
+        }
+    }
+
+    #[test]
+    fn overlapping_bounds_without_overlapping_triangles_is_no_contact() {
+        let a = collider(0, Triangles(vec![[Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(0.0, 10.0)]]));
+        let b = collider(1, Triangles(vec![[Vec2::new(10.0, 10.0), Vec2::new(10.0, 4.0), Vec2::new(4.0, 10.0)]]));
+
+        assert!(a.bounds.collision_with(&b.bounds));
+        assert_eq!(contact(&a, &b), None);
+    }
+
+    #[test]
+    fn contact_reports_penetration_depth_and_normal() {
+        let a = collider(0, CollisionBox { lower: Vec2::new(0.0, 0.0), upper: Vec2::new(10.0, 10.0) }.into());
+        let b = collider(1, CollisionBox { lower: Vec2::new(8.0, 1.0), upper: Vec2::new(18.0, 9.0) }.into());
+
+        let contact = contact(&a, &b).unwrap();
+
+        assert!((contact.depth - 2.0).abs() < 1e-4, "{}", contact.depth);
+        assert!(contact.normal.abs_diff_eq(Vec2::X, 1e-4), "{}", contact.normal);
+        assert_eq!(contact.swapped().normal, -contact.normal);
+    }
+
+    #[test]
+    fn sweep_reports_earliest_time_of_impact() {
+        let moving = CollisionBox { lower: Vec2::new(0.0, 0.0), upper: Vec2::new(10.0, 10.0) };
+        let thin = CollisionBox { lower: Vec2::new(50.0, 0.0), upper: Vec2::new(52.0, 10.0) };
+        let far = CollisionBox { lower: Vec2::new(80.0, 0.0), upper: Vec2::new(90.0, 10.0) };
+        let behind = CollisionBox { lower: Vec2::new(-30.0, 0.0), upper: Vec2::new(-20.0, 10.0) };
+
+        let time_of_impact = sweep(&moving, Vec2::new(100.0, 0.0), [&far, &thin, &behind]).unwrap();
+
+        assert!((time_of_impact - 0.4).abs() < 1e-4, "{}", time_of_impact);
+        assert_eq!(sweep(&moving, Vec2::new(30.0, 0.0), [&thin]), None);
+        assert_eq!(sweep(&moving, Vec2::new(0.0, 100.0), [&thin]), None);
+    }
+
+    #[test]
+    fn sweep_hits_a_target_it_already_overlaps() {
+        let moving = CollisionBox { lower: Vec2::new(0.0, 0.0), upper: Vec2::new(10.0, 10.0) };
+        let thin = CollisionBox { lower: Vec2::new(50.0, 0.0), upper: Vec2::new(52.0, 10.0) };
+        let motion = Vec2::new(100.0, 0.0);
+
+        let time_of_impact = sweep(&moving, motion, [&thin]).unwrap();
+        let skin = Vec2::new(0.5, 0.0);
+        let inside = CollisionBox {
+            lower: moving.lower + motion * time_of_impact + skin,
+            upper: moving.upper + motion * time_of_impact + skin,
+        };
+
+        assert!(inside.collision_with(&thin));
+        assert_eq!(sweep(&inside, motion, [&thin]), Some(0.0));
+    }
+
+    #[test]
+    fn sweep_triangles_passes_the_empty_corner_of_a_target() {
+        let moving = Triangles(vec![[Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(0.0, 10.0)]]);
+        let corner = Triangles(vec![[Vec2::new(60.0, 10.0), Vec2::new(60.0, 4.0), Vec2::new(54.0, 10.0)]]);
+        let short = Vec2::new(52.0, 0.0);
+
+        assert!(sweep(&moving.bounds(), short, [&corner.bounds()]).is_some());
+        assert_eq!(sweep_triangles(&moving, short, [&corner]), None);
+        let time_of_impact = sweep_triangles(&moving, Vec2::new(100.0, 0.0), [&corner]).unwrap();
+        assert!((0.54..=0.56).contains(&time_of_impact), "{}", time_of_impact);
+    }
+
+    /// Boxes of 5 to 45 units scattered over a 1000x1000 area, from a fixed seed
+    fn scattered_colliders(amount: usize) -> Vec<Collider> {
+        let mut seed: u32 = 12345;
+        let mut next = move || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            (seed >> 8) as f32 / (1 << 24) as f32
+        };
+        (0..amount)
+            .map(|entity| {
+                let lower = Vec2::new(next(), next()) * 1000.0;
+                let size = Vec2::new(next(), next()) * 40.0 + 5.0;
+                let bounds = CollisionBox { lower, upper: lower + size };
+                Collider {
+                    owner: Owner { entity, part: Part::Item },
+                    bounds,
+                    triangles: bounds.into(),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sweep_and_prune_finds_the_same_contacts_as_brute_force() {
+        let colliders = scattered_colliders(500);
+
+        let contacts = find_collisions(&colliders, &CollisionMasks::default());
+
+        assert!(!contacts.is_empty());
+        assert_eq!(contacts, find_collisions_brute_force(&colliders, &CollisionMasks::default()));
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `cargo test --release -- --ignored --nocapture`"]
+    fn benchmark_sweep_and_prune_against_brute_force() {
+        use std::time::Instant;
+        for amount in [50, 200, 1000, 4000] {
+            let colliders = scattered_colliders(amount);
+            let start = Instant::now();
+            let contacts = find_collisions(&colliders, &CollisionMasks::default());
+            let sweep_and_prune = start.elapsed();
+            let start = Instant::now();
+            let brute_force_contacts = find_collisions_brute_force(&colliders, &CollisionMasks::default());
+            let brute_force = start.elapsed();
+            assert_eq!(contacts, brute_force_contacts);
+            println!("{amount} colliders: sweep and prune {sweep_and_prune:?}, brute force {brute_force:?}");
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! Decides which colliders are allowed to collide. Every [Part] sits on a [Layer], and each layer has a mask listing
+//! the layers it collides with. The masks are read from a TOML file like
+//!
+//! ```toml
+//! player = ["wall", "item", "pickup"]
+//! pickup = ["player"]
+//! ```
+//!
+//! A pair is only tested if both layers list each other. Layers that are not listed in the file keep their default mask.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use super::Part;
+use crate::persistence::{self, FileType};
+
+pub const COLLISION_LAYERS_PATH: &str = "assets/config/collision_layers.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layer {
+    Player,
+    Hook,
+    Chain,
+    Item,
+    Wall,
+    /// Items on their way to the player
+    Pickup,
+}
+
+impl Part {
+    pub fn layer(&self) -> Layer {
+        match self {
+            Part::PlayerBody => Layer::Player,
+            Part::HookHead { .. } => Layer::Hook,
+            Part::ChainSegment { .. } => Layer::Chain,
+            Part::Item | Part::DroppedHook => Layer::Item,
+            Part::Pickup => Layer::Pickup,
+            Part::Wall => Layer::Wall,
+        }
+    }
+}
+
+/// Masks per layer, as written in the layers file
+pub type CollisionMasksFile = HashMap<Layer, Vec<Layer>>;
+
+#[derive(Debug, Clone)]
+pub struct CollisionMasks {
+    masks: HashMap<Layer, HashSet<Layer>>,
+}
+impl CollisionMasks {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let file = persistence::load::<CollisionMasksFile>(path, FileType::Toml)
+            .map_err(|e| anyhow!("Could not read collision layers from {path}: {e}"))?;
+        Ok(file.into())
+    }
+    /// Falls back to the default masks, and reports why, if the file cannot be used
+    pub fn load_or_default(path: &str) -> Self {
+        CollisionMasks::load(path).unwrap_or_else(|e| {
+            eprintln!("{e}. Using default collision layers");
+            CollisionMasks::default()
+        })
+    }
+    pub fn mask(&self, layer: Layer) -> Option<&HashSet<Layer>> {
+        self.masks.get(&layer)
+    }
+    pub fn collides(&self, a: Layer, b: Layer) -> bool {
+        let lists = |layer, other| self.mask(layer).is_some_and(|mask| mask.contains(&other));
+        lists(a, b) && lists(b, a)
+    }
+}
+impl Default for CollisionMasks {
+    fn default() -> Self {
+        use Layer::*;
+        let masks = HashMap::from([
+            (Player, HashSet::from([Hook, Chain, Item, Wall, Pickup])),
+            (Hook, HashSet::from([Player, Item, Wall])),
+            (Chain, HashSet::from([Player, Item])),
+            (Item, HashSet::from([Player, Hook, Chain, Item, Wall])),
+            (Wall, HashSet::from([Player, Hook, Item])),
+            (Pickup, HashSet::from([Player])),
+        ]);
+        CollisionMasks { masks }
+    }
+}
+impl From<CollisionMasksFile> for CollisionMasks {
+    fn from(file: CollisionMasksFile) -> Self {
+        let mut collision_masks = CollisionMasks::default();
+        for (layer, mask) in file {
+            collision_masks.masks.insert(layer, mask.into_iter().collect());
+        }
+        collision_masks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_layers_have_to_list_each_other() {
+        let file = CollisionMasksFile::from([(Layer::Pickup, vec![Layer::Player, Layer::Wall])]);
+        let masks = CollisionMasks::from(file);
+
+        assert!(masks.collides(Layer::Pickup, Layer::Player));
+        assert!(!masks.collides(Layer::Pickup, Layer::Wall));
+        assert!(masks.collides(Layer::Item, Layer::Hook));
+    }
+
+    #[test]
+    fn bundled_collision_layers_are_valid() {
+        CollisionMasks::load(COLLISION_LAYERS_PATH).unwrap();
+    }
+}
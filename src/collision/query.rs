@@ -0,0 +1,160 @@
+//! Questions about the world, answered from the colliders of [collision_boxes](super::collision_boxes).
+//! All queries test the triangles of each collider, not just its bounds, and return the indices of the state
+//! machines they touch.
+
+use itertools::Itertools;
+use macroquad::math::Vec2;
+
+use super::{Collider, CollisionBox, Triangles, penetration};
+use crate::model::{Direction, Position};
+
+/// Entities hit by a ray of length `max_length`, nearest first
+pub fn raycast(colliders: &[Collider], origin: Position, direction: Direction, max_length: f32) -> Vec<usize> {
+    let (origin, ray) = (origin.value(), direction.value() * max_length);
+    let end = origin + ray;
+    let ray_bounds = CollisionBox {
+        lower: origin.min(end),
+        upper: origin.max(end),
+    };
+    colliders
+        .iter()
+        .filter(|collider| overlaps_or_touches(&collider.bounds, &ray_bounds))
+        .filter_map(|collider| {
+            let distance = collider
+                .triangles
+                .0
+                .iter()
+                .filter_map(|triangle| ray_triangle(origin, ray, triangle))
+                .min_by(f32::total_cmp)?;
+            Some((collider.owner.entity, distance))
+        })
+        .sorted_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(entity, _)| entity)
+        .unique()
+        .collect()
+}
+
+/// Entities with a part inside, or partly inside, the circle
+pub fn overlap_circle(colliders: &[Collider], center: Position, radius: f32) -> Vec<usize> {
+    let center = center.value();
+    let circle_bounds = CollisionBox {
+        lower: center - radius,
+        upper: center + radius,
+    };
+    touched_entities(colliders, &circle_bounds, |triangle| {
+        contains(triangle, center) || edges(triangle).any(|(a, b)| distance_to_segment(center, a, b) <= radius)
+    })
+}
+
+/// Entities with a part inside, or partly inside, the box
+pub fn overlap_box(colliders: &[Collider], area: CollisionBox) -> Vec<usize> {
+    let area_triangles = Triangles::from(area);
+    touched_entities(colliders, &area, |triangle| {
+        area_triangles.0.iter().any(|area_triangle| penetration(area_triangle, triangle).is_some())
+    })
+}
+
+fn touched_entities(colliders: &[Collider], bounds: &CollisionBox, touches: impl Fn(&[Vec2; 3]) -> bool) -> Vec<usize> {
+    colliders
+        .iter()
+        .filter(|collider| overlaps_or_touches(&collider.bounds, bounds))
+        .filter(|collider| collider.triangles.0.iter().any(&touches))
+        .map(|collider| collider.owner.entity)
+        .unique()
+        .sorted()
+        .collect()
+}
+
+/// Unlike [CollisionBox::collision_with], boxes that only share an edge count. A ray along an axis has no width
+fn overlaps_or_touches(a: &CollisionBox, b: &CollisionBox) -> bool {
+    a.lower.x <= b.upper.x && b.lower.x <= a.upper.x && a.lower.y <= b.upper.y && b.lower.y <= a.upper.y
+}
+
+fn edges(triangle: &[Vec2; 3]) -> impl Iterator<Item = (Vec2, Vec2)> {
+    [(triangle[0], triangle[1]), (triangle[1], triangle[2]), (triangle[2], triangle[0])].into_iter()
+}
+
+fn contains(triangle: &[Vec2; 3], point: Vec2) -> bool {
+    let sides = edges(triangle).map(|(a, b)| (b - a).perp_dot(point - a)).collect_vec();
+    sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0)
+}
+
+fn distance_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let segment = b - a;
+    if segment == Vec2::ZERO {
+        return point.distance(a);
+    }
+    let t = ((point - a).dot(segment) / segment.length_squared()).clamp(0.0, 1.0);
+    point.distance(a + segment * t)
+}
+
+/// Distance along `ray` to where it first enters the triangle
+fn ray_triangle(origin: Vec2, ray: Vec2, triangle: &[Vec2; 3]) -> Option<f32> {
+    if contains(triangle, origin) {
+        return Some(0.0);
+    }
+    edges(triangle)
+        .filter_map(|(a, b)| {
+            let edge = b - a;
+            let denominator = ray.perp_dot(edge);
+            if denominator == 0.0 {
+                return None;
+            }
+            let t = (a - origin).perp_dot(edge) / denominator;
+            let u = (a - origin).perp_dot(ray) / denominator;
+            ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then_some(t * ray.length())
+        })
+        .min_by(f32::total_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::{Owner, Part};
+    use crate::model::RIGHT;
+
+    fn wall(entity: usize, lower: (f32, f32), upper: (f32, f32)) -> Collider {
+        let bounds = CollisionBox {
+            lower: lower.into(),
+            upper: upper.into(),
+        };
+        Collider {
+            owner: Owner { entity, part: Part::Wall },
+            bounds,
+            triangles: bounds.into(),
+        }
+    }
+
+    fn world() -> Vec<Collider> {
+        vec![
+            wall(0, (100.0, -10.0), (110.0, 10.0)),
+            wall(1, (50.0, -10.0), (60.0, 10.0)),
+            wall(2, (50.0, 40.0), (60.0, 60.0)),
+        ]
+    }
+
+    #[test]
+    fn raycast_returns_hits_nearest_first() {
+        let origin = Position::new(0.0, 0.0);
+
+        assert_eq!(raycast(&world(), origin, RIGHT, 200.0), vec![1, 0]);
+        assert_eq!(raycast(&world(), origin, RIGHT, 80.0), vec![1]);
+        assert!(raycast(&world(), origin, Direction::new(-1.0, 0.0), 200.0).is_empty());
+    }
+
+    #[test]
+    fn overlap_circle_touches_parts_within_radius() {
+        assert_eq!(overlap_circle(&world(), Position::new(55.0, 25.0), 16.0), vec![1, 2]);
+        assert!(overlap_circle(&world(), Position::new(80.0, 25.0), 10.0).is_empty());
+    }
+
+    #[test]
+    fn overlap_box_touches_overlapping_parts() {
+        let area = CollisionBox {
+            lower: Vec2::new(55.0, 0.0),
+            upper: Vec2::new(105.0, 5.0),
+        };
+
+        assert_eq!(overlap_box(&world(), area), vec![0, 1]);
+    }
+}
@@ -17,10 +17,32 @@ pub mod colors;
 pub mod graphics;
 
 //* Drawing */
-pub fn draw_states(states: &[StateMachineEnum]) {
+/// Draws `states` blended with the drawables of the previous simulation step.
+///
+/// `alpha` is how far the renderer is between the previous and the current step. Drawables are only
+/// interpolated when a state produced the same amount of drawables in both steps, e.g. not on the step a link is added
+pub fn draw_states(previous: &[Vec<Drawable>], states: &[StateMachineEnum], alpha: f32) {
     let origin = Position::from_vec(Vec2::new(mq::screen_width() / 2.0, mq::screen_height() / 2.0));
-    let drawables: Vec<Drawable> = states.iter().flat_map(StateMachineEnum::drawable).collect();
-    drawables.into_iter().for_each(draw_drawable);
+    drawables(states)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, current)| match previous.get(i) {
+            Some(previous) if previous.len() == current.len() => previous
+                .iter()
+                .zip(current)
+                .map(|(previous, Drawable { state, shape })| Drawable {
+                    state: previous.state.interpolate(&state, alpha),
+                    shape,
+                })
+                .collect(),
+            _ => current,
+        })
+        .for_each(draw_drawable);
+}
+
+/// Drawables grouped per state machine
+pub fn drawables(states: &[StateMachineEnum]) -> Vec<Vec<Drawable>> {
+    states.iter().map(StateMachineEnum::drawable).collect()
 }
 
 pub struct Drawable {
@@ -37,6 +59,7 @@ fn draw_drawable(Drawable { state, shape }: Drawable) {
     match shape {
         Shape::Rectangle(rectangle) => draw_rectangle(rectangle, position, direction),
         Shape::Circle(s) => draw_circle(s, position),
+        Shape::Ring(ring) => draw_ring(ring, position),
         Shape::Line(line) => draw_line(line, position, direction),
         Shape::Polygon(polygon) => draw_polygon(polygon, position, direction),
         Shape::Triangle(triangle) => draw_triangle(triangle, position, direction),
@@ -51,6 +74,10 @@ fn draw_circle(s: Circle, position: Position) {
     mq::draw_circle(position.x(), position.y(), s.radius.0, s.color().into());
 }
 
+fn draw_ring(ring: Ring, position: Position) {
+    mq::draw_circle_lines(position.x(), position.y(), ring.radius.0, ring.thickness, ring.color.into());
+}
+
 fn draw_polygon(polygon: Polygon, position: Position, direction: Direction) {
     let Polygon { radius, sides, color } = polygon;
     let rotation = Angle::<Degrees>::from(direction);
@@ -125,15 +152,14 @@ pub fn debug_draw_state_text(states: &[StateMachineEnum]) {
     mq::draw_multiline_text(debug_text.as_str(), 20.0, 20.0, 20.0, None, macroquad::color::RED);
 }
 
-pub fn debug_draw_collision_boxes(boxes: &[collision::CollisionBox], color: Color) {
-    boxes.iter().for_each(|b| {
-        draw_collision_box(b, color);
+pub fn debug_draw_collision_boxes(colliders: &[collision::Collider], color: Color) {
+    colliders.iter().for_each(|c| {
+        draw_collision_box(&c.bounds, color);
     });
 }
-pub fn debug_draw_collided_boxes(boxes: &[(collision::CollisionBox, collision::CollisionBox)], color: Color) {
-    boxes.iter().for_each(|p| {
-        draw_collision_box(&p.0, color);
-        draw_collision_box(&p.1, color);
+pub fn debug_draw_contacts(contacts: &[collision::Contact], color: Color) {
+    contacts.iter().for_each(|c| {
+        draw_collision_box(&c.overlap, color);
     });
 }
 
@@ -102,6 +102,7 @@ pub enum Shape {
     Rectangle(Rectangle),
     Triangle(Triangle),
     Circle(Circle),
+    Ring(Ring),
     Line(Line),
     #[default]
     Point,
@@ -127,6 +128,11 @@ impl From<Circle> for Shape {
         Shape::Circle(value)
     }
 }
+impl From<Ring> for Shape {
+    fn from(value: Ring) -> Self {
+        Shape::Ring(value)
+    }
+}
 impl From<Line> for Shape {
     fn from(value: Line) -> Self {
         Shape::Line(value)
@@ -325,6 +331,14 @@ impl Circle {
     }
 }
 
+/// Outline of a circle
+#[derive(Clone, Copy, Debug)]
+pub struct Ring {
+    pub radius: Radius,
+    pub thickness: f32,
+    pub color: Color,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct Line {
     pub length: f32,
@@ -27,6 +27,13 @@ pub const PLAYER_GRAPHICS_2: PlayerGraphics = PlayerGraphics {
     color: PLAYER_COLOR,
 };
 
+/// Drawn around the player while a throw is charged. Reaches this radius at full charge
+pub const CHARGE_RING: Ring = Ring {
+    radius: Radius(PLAYER_SCALE * 3.0),
+    thickness: 2.0,
+    color: GOLD,
+};
+
 pub struct Animation {
     pub index: usize,
     pub frames: [PlayerGraphics; 2],
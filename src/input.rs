@@ -1,24 +1,163 @@
-use macroquad::input::*;
+use macroquad::input::mouse_position;
+use macroquad::math::Vec2;
+use serde::{Deserialize, Serialize};
+
 use crate::model::*;
+use key_bindings::{InputAction, KeyBindings};
+
+pub mod key_bindings;
 
-//* Utility functions */
+/// Snapshot of the player input for a single simulation step
+#[derive(Debug, Default, Clone, Copy)]
+pub struct InputFrame {
+    pub movement: Direction,
+    /// Cursor in world coordinates. Without it the player faces, and shoots, where it moves
+    pub aim: Option<Position>,
+    pub shoot: ButtonState,
+    /// Throws one of the hooks in the off hand
+    pub shoot_off_hand: ButtonState,
+    pub dash: ButtonState,
+    /// Pulls an extending hook back
+    pub retract: ButtonState,
+    pub pause: ButtonState,
+}
 
-pub fn get_player_move() -> Direction {
-    let mut direction = Direction::new(0.0, 0.0);
-    if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
-        direction = direction + UP;
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ButtonState {
+    /// Went down since the previous step
+    pub pressed: bool,
+    /// Is currently down
+    pub held: bool,
+}
+impl ButtonState {
+    pub fn pressed() -> Self {
+        ButtonState { pressed: true, held: true }
+    }
+    pub fn held() -> Self {
+        ButtonState { pressed: false, held: true }
+    }
+    /// Went down and back up within the same step
+    pub fn tapped() -> Self {
+        ButtonState { pressed: true, held: false }
+    }
+}
+
+/// Where the hook is shot
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AimMode {
+    /// Along the movement direction
+    #[default]
+    Movement,
+    /// Toward the mouse cursor
+    Mouse,
+}
+
+/// Provides one [InputFrame] per simulation step
+pub trait InputSource {
+    fn next_frame(&mut self) -> InputFrame;
+}
+
+/// Reads the keyboard. Requires a macroquad window
+///
+/// [KeyboardInput::poll] must be called once per rendered frame. Presses are kept until a step has consumed them,
+/// since a rendered frame can run zero or several simulation steps
+#[derive(Debug)]
+pub struct KeyboardInput {
+    bindings: KeyBindings,
+    aim_mode: AimMode,
+    frame: InputFrame,
+}
+impl KeyboardInput {
+    /// Aims the way `bindings` asks for
+    pub fn new(bindings: KeyBindings) -> Self {
+        KeyboardInput { aim_mode: bindings.aim_mode(), bindings, frame: InputFrame::default() }
+    }
+    pub fn poll(&mut self) {
+        let InputFrame { shoot, shoot_off_hand, dash, retract, pause, .. } = self.frame;
+        self.frame = InputFrame {
+            movement: self.get_player_move(),
+            aim: self.get_aim(),
+            shoot: self.poll_button(InputAction::Shoot, shoot),
+            shoot_off_hand: self.poll_button(InputAction::ShootOffHand, shoot_off_hand),
+            dash: self.poll_button(InputAction::Dash, dash),
+            retract: self.poll_button(InputAction::Retract, retract),
+            pause: self.poll_button(InputAction::Pause, pause),
+        };
     }
-    if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
-        direction = direction + LEFT;
+    fn poll_button(&self, action: InputAction, previous: ButtonState) -> ButtonState {
+        ButtonState {
+            pressed: previous.pressed || self.bindings.is_pressed(action),
+            held: self.bindings.is_down(action),
+        }
     }
-    if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
-        direction = direction + DOWN;
+    fn get_player_move(&self) -> Direction {
+        let mut direction = Direction::new(0.0, 0.0);
+        if self.bindings.is_down(InputAction::MoveUp) {
+            direction = direction + UP;
+        }
+        if self.bindings.is_down(InputAction::MoveLeft) {
+            direction = direction + LEFT;
+        }
+        if self.bindings.is_down(InputAction::MoveDown) {
+            direction = direction + DOWN;
+        }
+        if self.bindings.is_down(InputAction::MoveRight) {
+            direction = direction + RIGHT;
+        }
+        direction.normalize_or_zero()
     }
-    if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
-        direction = direction + RIGHT;
+    /// The world is drawn unscaled from the top left of the window, so the cursor is already in world coordinates
+    fn get_aim(&self) -> Option<Position> {
+        match self.aim_mode {
+            AimMode::Movement => None,
+            AimMode::Mouse => Some(Position::from_vec(Vec2::from(mouse_position()))),
+        }
+    }
+}
+impl Default for KeyboardInput {
+    fn default() -> Self {
+        KeyboardInput::new(KeyBindings::default())
+    }
+}
+impl InputSource for KeyboardInput {
+    fn next_frame(&mut self) -> InputFrame {
+        let frame = self.frame;
+        let InputFrame { shoot, shoot_off_hand, dash, retract, pause, .. } = &mut self.frame;
+        for button in [shoot, shoot_off_hand, dash, retract, pause] {
+            button.pressed = false;
+        }
+        frame
+    }
+}
+
+/// Plays back a list of frames, one per step. Yields empty frames when the script has run out
+#[derive(Debug, Default)]
+pub struct ScriptedInput {
+    frames: Vec<InputFrame>,
+    index: usize,
+}
+impl ScriptedInput {
+    pub fn new(frames: Vec<InputFrame>) -> Self {
+        ScriptedInput { frames, index: 0 }
+    }
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.frames.len()
+    }
+}
+impl InputSource for ScriptedInput {
+    fn next_frame(&mut self) -> InputFrame {
+        let frame = self.frames.get(self.index).copied().unwrap_or_default();
+        self.index += 1;
+        frame
+    }
+}
+
+/// No input at all
+#[derive(Debug, Default)]
+pub struct NullInput;
+impl InputSource for NullInput {
+    fn next_frame(&mut self) -> InputFrame {
+        InputFrame::default()
     }
-    direction.normalize_or_zero()
 }
-pub fn is_shooting() -> bool {
-    is_key_pressed(KeyCode::Space)
-}
\ No newline at end of file
@@ -0,0 +1,203 @@
+//! Maps logical input actions to keys. The bindings are read from a TOML file like
+//!
+//! ```toml
+//! move_up = ["W", "Up"]
+//! shoot = ["Space"]
+//! ```
+//!
+//! Key names are the names of macroquad's [KeyCode] variants, compared case-insensitively.
+//! Actions that are not listed in the file keep their default keys. The file can also pick the [AimMode], with
+//! `aim_mode = "mouse"` or `aim_mode = "movement"`.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use macroquad::input::{KeyCode, is_key_down, is_key_pressed};
+use serde::{Deserialize, Serialize};
+
+use crate::input::AimMode;
+use crate::persistence::{self, FileType};
+
+pub const KEY_BINDINGS_PATH: &str = "assets/config/key_bindings.toml";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Shoot,
+    ShootOffHand,
+    Dash,
+    Retract,
+    Pause,
+}
+
+/// Key names per action, as written in the bindings file
+pub type KeyBindingsFile = HashMap<InputAction, Vec<String>>;
+
+/// The whole bindings file: the keys, next to the aim mode
+#[derive(Debug, Deserialize)]
+struct InputSettingsFile {
+    #[serde(default)]
+    aim_mode: AimMode,
+    #[serde(flatten)]
+    keys: KeyBindingsFile,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    bindings: HashMap<InputAction, Vec<KeyCode>>,
+    aim_mode: AimMode,
+}
+impl KeyBindings {
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        let file = persistence::load::<InputSettingsFile>(path, FileType::Toml)
+            .map_err(|e| anyhow!("Could not read key bindings from {path}: {e}"))?;
+        let key_bindings =
+            KeyBindings::try_from(file.keys).map_err(|e| anyhow!("Invalid key bindings in {path}: {e}"))?;
+        Ok(KeyBindings { aim_mode: file.aim_mode, ..key_bindings })
+    }
+    /// Falls back to the default bindings, and reports why, if the file cannot be used
+    pub fn load_or_default(path: &str) -> Self {
+        KeyBindings::load(path).unwrap_or_else(|e| {
+            eprintln!("{e}. Using default key bindings");
+            KeyBindings::default()
+        })
+    }
+    pub fn aim_mode(&self) -> AimMode {
+        self.aim_mode
+    }
+    pub fn keys(&self, action: InputAction) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+    /// Requires a macroquad window
+    pub fn is_down(&self, action: InputAction) -> bool {
+        self.keys(action).iter().any(|key| is_key_down(*key))
+    }
+    /// Requires a macroquad window
+    pub fn is_pressed(&self, action: InputAction) -> bool {
+        self.keys(action).iter().any(|key| is_key_pressed(*key))
+    }
+}
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let bindings = HashMap::from([
+            (InputAction::MoveUp, vec![KeyCode::W, KeyCode::Up]),
+            (InputAction::MoveDown, vec![KeyCode::S, KeyCode::Down]),
+            (InputAction::MoveLeft, vec![KeyCode::A, KeyCode::Left]),
+            (InputAction::MoveRight, vec![KeyCode::D, KeyCode::Right]),
+            (InputAction::Shoot, vec![KeyCode::Space]),
+            (InputAction::ShootOffHand, vec![KeyCode::F]),
+            (InputAction::Dash, vec![KeyCode::LeftShift]),
+            (InputAction::Retract, vec![KeyCode::Q]),
+            (InputAction::Pause, vec![KeyCode::Escape]),
+        ]);
+        KeyBindings { bindings, aim_mode: AimMode::default() }
+    }
+}
+impl TryFrom<KeyBindingsFile> for KeyBindings {
+    type Error = anyhow::Error;
+
+    /// All unknown key names are reported at once
+    fn try_from(file: KeyBindingsFile) -> Result<Self, Self::Error> {
+        let mut key_bindings = KeyBindings::default();
+        let mut unknown = vec![];
+        for (action, names) in file {
+            let keys = names
+                .iter()
+                .filter_map(|name| {
+                    let key = key_code_from_name(name);
+                    if key.is_none() {
+                        unknown.push(format!("\"{name}\" (bound to {action:?})"));
+                    }
+                    key
+                })
+                .collect();
+            key_bindings.bindings.insert(action, keys);
+        }
+        if unknown.is_empty() {
+            Ok(key_bindings)
+        } else {
+            Err(anyhow!("unknown key names {}", unknown.join(", ")))
+        }
+    }
+}
+
+pub fn key_code_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+const KEY_NAMES: [(&str, KeyCode); 121] = [
+    ("Space", KeyCode::Space), ("Apostrophe", KeyCode::Apostrophe), ("Comma", KeyCode::Comma),
+    ("Minus", KeyCode::Minus), ("Period", KeyCode::Period), ("Slash", KeyCode::Slash), ("Key0", KeyCode::Key0),
+    ("Key1", KeyCode::Key1), ("Key2", KeyCode::Key2), ("Key3", KeyCode::Key3), ("Key4", KeyCode::Key4),
+    ("Key5", KeyCode::Key5), ("Key6", KeyCode::Key6), ("Key7", KeyCode::Key7), ("Key8", KeyCode::Key8),
+    ("Key9", KeyCode::Key9), ("Semicolon", KeyCode::Semicolon), ("Equal", KeyCode::Equal), ("A", KeyCode::A),
+    ("B", KeyCode::B), ("C", KeyCode::C), ("D", KeyCode::D), ("E", KeyCode::E), ("F", KeyCode::F), ("G", KeyCode::G),
+    ("H", KeyCode::H), ("I", KeyCode::I), ("J", KeyCode::J), ("K", KeyCode::K), ("L", KeyCode::L), ("M", KeyCode::M),
+    ("N", KeyCode::N), ("O", KeyCode::O), ("P", KeyCode::P), ("Q", KeyCode::Q), ("R", KeyCode::R), ("S", KeyCode::S),
+    ("T", KeyCode::T), ("U", KeyCode::U), ("V", KeyCode::V), ("W", KeyCode::W), ("X", KeyCode::X), ("Y", KeyCode::Y),
+    ("Z", KeyCode::Z), ("LeftBracket", KeyCode::LeftBracket), ("Backslash", KeyCode::Backslash),
+    ("RightBracket", KeyCode::RightBracket), ("GraveAccent", KeyCode::GraveAccent), ("World1", KeyCode::World1),
+    ("World2", KeyCode::World2), ("Escape", KeyCode::Escape), ("Enter", KeyCode::Enter), ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace), ("Insert", KeyCode::Insert), ("Delete", KeyCode::Delete),
+    ("Right", KeyCode::Right), ("Left", KeyCode::Left), ("Down", KeyCode::Down), ("Up", KeyCode::Up),
+    ("PageUp", KeyCode::PageUp), ("PageDown", KeyCode::PageDown), ("Home", KeyCode::Home), ("End", KeyCode::End),
+    ("CapsLock", KeyCode::CapsLock), ("ScrollLock", KeyCode::ScrollLock), ("NumLock", KeyCode::NumLock),
+    ("PrintScreen", KeyCode::PrintScreen), ("Pause", KeyCode::Pause), ("F1", KeyCode::F1), ("F2", KeyCode::F2),
+    ("F3", KeyCode::F3), ("F4", KeyCode::F4), ("F5", KeyCode::F5), ("F6", KeyCode::F6), ("F7", KeyCode::F7),
+    ("F8", KeyCode::F8), ("F9", KeyCode::F9), ("F10", KeyCode::F10), ("F11", KeyCode::F11), ("F12", KeyCode::F12),
+    ("F13", KeyCode::F13), ("F14", KeyCode::F14), ("F15", KeyCode::F15), ("F16", KeyCode::F16), ("F17", KeyCode::F17),
+    ("F18", KeyCode::F18), ("F19", KeyCode::F19), ("F20", KeyCode::F20), ("F21", KeyCode::F21), ("F22", KeyCode::F22),
+    ("F23", KeyCode::F23), ("F24", KeyCode::F24), ("F25", KeyCode::F25), ("Kp0", KeyCode::Kp0), ("Kp1", KeyCode::Kp1),
+    ("Kp2", KeyCode::Kp2), ("Kp3", KeyCode::Kp3), ("Kp4", KeyCode::Kp4), ("Kp5", KeyCode::Kp5), ("Kp6", KeyCode::Kp6),
+    ("Kp7", KeyCode::Kp7), ("Kp8", KeyCode::Kp8), ("Kp9", KeyCode::Kp9), ("KpDecimal", KeyCode::KpDecimal),
+    ("KpDivide", KeyCode::KpDivide), ("KpMultiply", KeyCode::KpMultiply), ("KpSubtract", KeyCode::KpSubtract),
+    ("KpAdd", KeyCode::KpAdd), ("KpEnter", KeyCode::KpEnter), ("KpEqual", KeyCode::KpEqual),
+    ("LeftShift", KeyCode::LeftShift), ("LeftControl", KeyCode::LeftControl), ("LeftAlt", KeyCode::LeftAlt),
+    ("LeftSuper", KeyCode::LeftSuper), ("RightShift", KeyCode::RightShift), ("RightControl", KeyCode::RightControl),
+    ("RightAlt", KeyCode::RightAlt), ("RightSuper", KeyCode::RightSuper), ("Menu", KeyCode::Menu),
+    ("Back", KeyCode::Back),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listed_actions_replace_defaults() {
+        let file = KeyBindingsFile::from([(InputAction::Shoot, vec!["j".to_string(), "Enter".to_string()])]);
+        let key_bindings = KeyBindings::try_from(file).unwrap();
+
+        assert_eq!(key_bindings.keys(InputAction::Shoot), [KeyCode::J, KeyCode::Enter]);
+        assert_eq!(key_bindings.keys(InputAction::MoveUp), [KeyCode::W, KeyCode::Up]);
+    }
+
+    #[test]
+    fn unknown_key_names_are_reported() {
+        let file = KeyBindingsFile::from([(InputAction::Dash, vec!["Shift".to_string()])]);
+        let error = KeyBindings::try_from(file).unwrap_err().to_string();
+
+        assert!(error.contains("\"Shift\" (bound to Dash)"), "{error}");
+    }
+
+    #[test]
+    fn bundled_key_bindings_are_valid() {
+        KeyBindings::load(KEY_BINDINGS_PATH).unwrap();
+    }
+
+    #[test]
+    fn aim_mode_is_read_next_to_the_keys() {
+        let settings: InputSettingsFile = toml::from_str("aim_mode = \"mouse\"\nshoot = [\"J\"]").unwrap();
+        assert_eq!(settings.aim_mode, AimMode::Mouse);
+        assert_eq!(settings.keys[&InputAction::Shoot], ["J"]);
+
+        let settings: InputSettingsFile = toml::from_str("shoot = [\"J\"]").unwrap();
+        assert_eq!(settings.aim_mode, AimMode::Movement);
+    }
+}
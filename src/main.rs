@@ -3,16 +3,17 @@
 use std::thread::sleep;
 use std::time::Duration;
 
+use crate::collision::collision_boxes;
+use crate::collision::layers::*;
 use crate::collision::collisions;
 use crate::colors::*;
 use crate::draw::*;
+use crate::input::InputSource;
+use crate::input::KeyboardInput;
+use crate::input::key_bindings::*;
 use crate::model::*;
+use crate::simulation::*;
 use crate::state::StateMachineEnum;
-use crate::state::player::PLAYER_SPEED;
-use crate::state::player::PlayerStateMachine;
-use crate::state::item::*;
-use crate::state::state_machine::item::ItemState;
-use crate::state::state_machine::item::build;
 use macroquad::math::Vec2;
 use macroquad::prelude as mq;
 use macroquad::window::screen_height;
@@ -22,6 +23,7 @@ mod draw;
 mod input;
 mod model;
 mod persistence;
+mod simulation;
 mod state;
 mod util;
 mod collision;
@@ -39,16 +41,29 @@ async fn main() {
     mq::request_new_screen_size(DRAW_SCREEN_WIDTH, DRAW_SCREEN_HEIGHT);
     // set_pc_assets_folder("assets");
 
-    let mut states = vec![init_player(), init_item()];
+    let mut states = init_states();
+    let mut previous = drawables(&states);
+    let masks = CollisionMasks::load_or_default(COLLISION_LAYERS_PATH);
+    let mut contacts = collisions(&states, &masks);
+    let mut accumulator = 0.0;
+    let mut input = KeyboardInput::new(KeyBindings::load_or_default(KEY_BINDINGS_PATH));
 
     loop {
-        // let delta_time = get_frame_time();
-        // Use like "MOVEMENT_SPEED * delta_time;"
+        let steps;
+        (steps, accumulator) = fixed_steps(accumulator, mq::get_frame_time());
+        input.poll();
+        for _ in 0..steps {
+            previous = drawables(&states);
+            contacts = step(&mut states, FIXED_TIME_STEP, input.next_frame(), &masks);
+        }
 
         mq::clear_background(BLACK.into());
-        update_states(&mut states);
-        draw_states(&states);
-        collisions(&states);
+        draw_states(&previous, &states, accumulator / FIXED_TIME_STEP);
+
+        if DEBUG_DRAW_COLLISION_BOXES {
+            debug_draw_collision_boxes(&collision_boxes(&states), RED);
+            debug_draw_contacts(&contacts, GREEN);
+        }
 
         if DEBUG_DRAW_STATE_TEXT {
             debug_draw_state_text(&states);
@@ -59,19 +74,3 @@ async fn main() {
         mq::next_frame().await
     }
 }
-
-fn init_player() -> StateMachineEnum {
-    StateMachineEnum::Player(PlayerStateMachine::new(Position::new(200.0, 200.0), RIGHT, PLAYER_SPEED))
-}
-
-fn init_item() -> StateMachineEnum {
-    StateMachineEnum::Item(ItemStateMachine::Moving(build(Position::new(200.0, 200.0), RIGHT, Magnitude::new(1.0))))
-}
-
-fn update_states(states: &mut [StateMachineEnum]) {
-    for state in states.iter_mut() {
-        let s1 = std::mem::take(state);
-        let s2 = s1.update();
-        *state = s2;
-    }
-}
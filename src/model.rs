@@ -6,6 +6,7 @@ use std::{
 };
 
 use crate::graphics::*;
+use crate::persistence::{self, FileType};
 use anyhow::*;
 use macroquad::{
     math::Vec2,
@@ -22,42 +23,76 @@ pub const RIGHT: Direction = Direction(Vec2::X);
 pub trait Object: Sized + core::fmt::Debug + Copy + Display {
     fn position(&self) -> Position;
     fn set_position(&mut self, position: &Position);
-    fn update_position(&mut self, v: Magnitude, direction: Direction) {
-        self.set_position(&self.physics().calculate_new_position(self.position(), v, direction));
+    fn update_position(&mut self, v: Magnitude, direction: Direction, dt: f32) {
+        self.set_position(&self.physics().calculate_new_position(self.position(), v, direction, dt));
     }
     fn physics(&self) -> &Physics;
     fn direction(&self) -> &Direction;
     fn set_direction(&mut self, direction: &Direction);
 }
 
-#[derive(Serialize, Deserialize, Default, TypedBuilder, Clone, Copy, Debug)]
+/// Movement parameters. Speeds are in units per second, `acceleration` and `deceleration` in units per second squared
+/// and `turn_rate` in degrees per second
+#[derive(Serialize, Deserialize, Default, TypedBuilder, Clone, Copy, Debug, PartialEq)]
 pub struct Physics {
+    /// Below this speed the object comes to a halt and turns freely
     pub min_speed: f32,
     pub max_speed: f32,
     pub acceleration: f32,
+    pub deceleration: f32,
+    pub turn_rate: f32,
 }
 
 impl Physics {
+    pub fn load(path: &str) -> Result<Self> {
+        persistence::load::<Physics>(path, FileType::Toml).map_err(|e| anyhow!("Could not read physics from {path}: {e}"))
+    }
+    /// Falls back to `default`, and reports why, if the file cannot be used
+    pub fn load_or(path: &str, default: Physics) -> Self {
+        Physics::load(path).unwrap_or_else(|e| {
+            eprintln!("{e}. Using default physics");
+            default
+        })
+    }
+
     pub fn accelerate(&self, current_speed: Magnitude, elapsed_time: f32) -> Magnitude {
         let v = current_speed.value();
         self.max_speed.min(v + self.acceleration * elapsed_time).into()
     }
 
+    /// Slows down by friction until the speed drops below `min_speed`
+    pub fn decelerate(&self, current_speed: Magnitude, elapsed_time: f32) -> Magnitude {
+        let v = current_speed.value() - self.deceleration * elapsed_time;
+        if v < self.min_speed { Magnitude::zero() } else { v.into() }
+    }
+
+    /// Turns `direction` toward `target`, limited by `turn_rate` while moving
+    pub fn turn(&self, direction: Direction, target: Direction, current_speed: Magnitude, elapsed_time: f32) -> Direction {
+        if current_speed.value() < self.min_speed {
+            target
+        } else {
+            direction.rotate_towards(target, Angle(Degrees(self.turn_rate * elapsed_time)).to_radians())
+        }
+    }
+
     pub fn calculate_new_position(
         &self,
         position: Position,
         speed: Magnitude,
         direction: Direction,
+        dt: f32,
     ) -> Position {
-        Self::calculate_new_position_from_speed(position, speed, direction)
+        Self::calculate_new_position_from_speed(position, speed, direction, dt)
     }
 
+    /// `speed` is given in units per second, `dt` is the elapsed time in seconds
     pub fn calculate_new_position_from_speed(
         position: Position,
         speed: Magnitude,
         direction: Direction,
+        dt: f32,
     ) -> Position {
-        let velocity = direction * speed;
+        let velocity = direction * (speed * dt);
         Position::new(position.x() + velocity.x(), position.y() + velocity.y())
     }
 
@@ -96,6 +131,9 @@ impl Position {
     pub fn move_in_direction(self, direction: Direction, distance: f32) -> Self {
         self + (direction * distance)
     }
+    pub fn lerp(self, position: Position, t: f32) -> Self {
+        Self(self.value().lerp(position.value(), t))
+    }
 }
 impl Display for Position {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -135,6 +173,10 @@ impl Direction {
     pub fn is_zero(&self) -> bool {
         self.value().x == 0.0 && self.value().y == 0.0
     }
+    /// `self`, unless it is zero
+    pub fn or(self, direction: Direction) -> Self {
+        if self.is_zero() { direction } else { self }
+    }
     pub fn x(&self) -> f32 {
         self.value().x
     }
@@ -153,6 +195,19 @@ impl Direction {
     {
         rotate_by_direction(self, angle.into())
     }
+    /// Rotates toward `target` by at most `max_angle`
+    pub fn rotate_towards(self, target: Direction, max_angle: Angle<Radians>) -> Self {
+        if self.is_zero() || target.is_zero() {
+            return target.or(self);
+        }
+        let max_angle = max_angle.value().value();
+        let angle = self.value().angle_between(target.value()).clamp(-max_angle, max_angle);
+        self.rotate(Angle(Radians(angle)))
+    }
+    /// Falls back to `direction` if the interpolated vector is zero (i.e. opposite directions)
+    pub fn lerp(self, direction: Direction, t: f32) -> Self {
+        Direction::new_vec(self.value().lerp(direction.value(), t)).or(direction)
+    }
 }
 impl Display for Direction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -314,7 +369,7 @@ impl Magnitude {
     pub const fn zero() -> Magnitude {
         Magnitude(0.0)
     }
-    pub fn value(&self) -> f32 {
+    pub const fn value(&self) -> f32 {
         self.0
     }
 }
@@ -356,6 +411,13 @@ impl Add<Magnitude> for f32 {
         Magnitude(self + rhs.0)
     }
 }
+impl Mul<f32> for Magnitude {
+    type Output = Magnitude;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        Magnitude(self.0 * rhs)
+    }
+}
 
 impl<T> Mul<T> for Direction
 where
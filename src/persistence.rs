@@ -5,6 +5,15 @@ pub enum FileType {
     Json,
     Toml,
 }
+impl FileType {
+    /// JSON for `.json` files, TOML for everything else
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some("json") => FileType::Json,
+            _ => FileType::Toml,
+        }
+    }
+}
 
 pub fn load<T>(path: &str, file_type: FileType) -> Result<T, anyhow::Error> where T: Sized + DeserializeOwned {
     match file_type {
@@ -0,0 +1,614 @@
+//! Steps the world independently of macroquad's window, graphics and input.
+//!
+//! The game loop in `main` and the headless runner share [step], so anything that works here works in the game.
+
+use std::cell::RefCell;
+
+use crate::collision::layers::CollisionMasks;
+use crate::collision::{CollisionBox, Contact, Triangles, collisions, resolve_contacts};
+use crate::input::{InputFrame, InputSource};
+use crate::model::*;
+use crate::state::hook::{HOOK_SPEC, HOOK_SPEC_PATH, HookHead};
+use crate::state::item::ItemStateMachine;
+use crate::state::player::{PLAYER_PHYSICS, PLAYER_PHYSICS_PATH, Player, PlayerStateMachine};
+use crate::state::state_machine::hook::HookSpec;
+use crate::state::state_machine::item::build;
+use crate::state::wall::Wall;
+use crate::state::{StateMachineEnum, Tick};
+
+/// Duration of a single simulation step in seconds
+pub const FIXED_TIME_STEP: f32 = 1.0 / 60.0;
+/// Upper limit on frame time fed to the simulation, so a long stall does not result in a burst of steps
+pub const MAX_FRAME_TIME: f32 = 0.25;
+
+/// How many fixed steps a rendered frame that took `frame_time` seconds calls for, and the time left over for the
+/// next frame. `accumulator` is what the previous frame left over
+pub fn fixed_steps(accumulator: f32, frame_time: f32) -> (usize, f32) {
+    let mut accumulator = accumulator + frame_time.min(MAX_FRAME_TIME);
+    let mut steps = 0;
+    while accumulator >= FIXED_TIME_STEP {
+        accumulator -= FIXED_TIME_STEP;
+        steps += 1;
+    }
+    (steps, accumulator)
+}
+
+pub fn init_states() -> Vec<StateMachineEnum> {
+    let physics = Physics::load_or(PLAYER_PHYSICS_PATH, PLAYER_PHYSICS);
+    let hook = HookSpec::load_or(HOOK_SPEC_PATH, HOOK_SPEC);
+    vec![init_player(physics, hook), init_item(), init_wall()]
+}
+
+fn init_player(physics: Physics, hook: HookSpec) -> StateMachineEnum {
+    StateMachineEnum::Player(Player::new(Position::new(200.0, 200.0), RIGHT, physics, hook))
+}
+
+fn init_item() -> StateMachineEnum {
+    StateMachineEnum::Item(ItemStateMachine::Moving(build(Position::new(200.0, 200.0), RIGHT, Magnitude::new(60.0))))
+}
+
+fn init_wall() -> StateMachineEnum {
+    StateMachineEnum::Wall(Wall::new(Position::new(700.0, 200.0), 40.0, 300.0))
+}
+
+/// Advances all states by one step, lets them react to the contacts of the new world and returns those contacts
+pub fn step(states: &mut Vec<StateMachineEnum>, dt: f32, input: InputFrame, masks: &CollisionMasks) -> Vec<Contact> {
+    let obstacles = obstacles(states);
+    let items = catchable_items(states);
+    let hooks = hook_heads(states);
+    let spawned = RefCell::new(vec![]);
+    let tick = Tick {
+        dt,
+        input,
+        obstacles: &obstacles,
+        items: &items,
+        hooks: &hooks,
+        masks,
+        spawned: &spawned,
+    };
+    update_states(states, &tick);
+    states.append(&mut spawned.into_inner());
+    let contacts = collisions(states, masks);
+    resolve_contacts(states, &contacts);
+    contacts
+}
+
+fn obstacles(states: &[StateMachineEnum]) -> Vec<CollisionBox> {
+    states
+        .iter()
+        .filter_map(|state| match state {
+            StateMachineEnum::Wall(wall) => Some(wall.bounds()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn catchable_items(states: &[StateMachineEnum]) -> Vec<Triangles> {
+    states
+        .iter()
+        .filter_map(|state| match state {
+            StateMachineEnum::Item(item) => item.catchable_triangles(),
+            _ => None,
+        })
+        .collect()
+}
+
+fn hook_heads(states: &[StateMachineEnum]) -> Vec<HookHead> {
+    states
+        .iter()
+        .enumerate()
+        .flat_map(|(entity, state)| match state {
+            StateMachineEnum::Player(player) => player.hook_heads(entity),
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Runs `ticks` fixed steps without a macroquad window, taking one input frame per step.
+/// Uses the default collision masks
+pub fn run_headless(
+    mut states: Vec<StateMachineEnum>,
+    ticks: usize,
+    input: &mut impl InputSource,
+) -> Vec<StateMachineEnum> {
+    let masks = CollisionMasks::default();
+    for _ in 0..ticks {
+        step(&mut states, FIXED_TIME_STEP, input.next_frame(), &masks);
+    }
+    states
+}
+
+fn update_states(states: &mut [StateMachineEnum], tick: &Tick) {
+    for state in states.iter_mut() {
+        let s1 = std::mem::take(state);
+        let s2 = s1.update(tick);
+        *state = s2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::layers::{CollisionMasksFile, Layer};
+    use crate::collision::{Collision, Owner, Part, collision_boxes};
+    use crate::draw::Draw;
+    use crate::draw::graphics::Shape;
+    use crate::input::{ButtonState, InputFrame, NullInput, ScriptedInput};
+    use crate::state::StateMachine;
+    use crate::state::state_machine::hook::HookState;
+    use crate::state::state_machine::{
+        CHARGE_DURATION, CHARGE_MAX_FACTOR, DASH_DURATION, DASH_INVULNERABLE_DURATION, DASH_SPEED, OFF_HAND_HOOKS,
+        State,
+    };
+
+    fn repeat(frame: InputFrame, ticks: usize) -> ScriptedInput {
+        ScriptedInput::new(vec![frame; ticks])
+    }
+
+    fn player() -> Vec<StateMachineEnum> {
+        vec![init_player(PLAYER_PHYSICS, HOOK_SPEC)]
+    }
+
+    fn player_state(states: &[StateMachineEnum]) -> &PlayerStateMachine {
+        match &states[0] {
+            StateMachineEnum::Player(player) => player.parent(),
+            _ => panic!("First state should be the player"),
+        }
+    }
+
+    fn main_hook(states: &[StateMachineEnum]) -> Option<&HookState> {
+        match &states[0] {
+            StateMachineEnum::Player(player) => player.main_hook(),
+            _ => panic!("First state should be the player"),
+        }
+    }
+
+    #[test]
+    fn player_runs_in_input_direction() {
+        let input = InputFrame { movement: RIGHT, ..Default::default() };
+        let states = run_headless(player(), 60, &mut repeat(input, 60));
+
+        let position = states[0].state_object()[0].position;
+        let ramp_up = PLAYER_PHYSICS.max_speed / PLAYER_PHYSICS.acceleration;
+        let expected = 200.0 + PLAYER_PHYSICS.max_speed * (1.0 - ramp_up / 2.0);
+        assert!((position.x() - expected).abs() < 2.0, "{}", position);
+        assert_eq!(position.y(), 200.0);
+    }
+
+    #[test]
+    fn player_accelerates_then_coasts_to_a_halt() {
+        let input = InputFrame { movement: RIGHT, ..Default::default() };
+        let states = run_headless(player(), 1, &mut repeat(input, 1));
+        let first_step = states[0].state_object()[0].position.x() - 200.0;
+        assert!(first_step < PLAYER_PHYSICS.max_speed * FIXED_TIME_STEP, "{}", first_step);
+
+        let states = run_headless(states, 59, &mut repeat(input, 59));
+        let released_at = states[0].state_object()[0].position;
+        let states = run_headless(states, 1, &mut NullInput);
+        let coasted_to = states[0].state_object()[0].position;
+        assert!(coasted_to.x() > released_at.x(), "{} {}", released_at, coasted_to);
+
+        let states = run_headless(states, 30, &mut NullInput);
+        let halted_at = states[0].state_object()[0].position;
+        let states = run_headless(states, 1, &mut NullInput);
+        assert_eq!(states[0].state_object()[0].position, halted_at);
+    }
+
+    #[test]
+    fn running_player_turns_with_momentum() {
+        let right = InputFrame { movement: RIGHT, ..Default::default() };
+        let states = run_headless(player(), 60, &mut repeat(right, 60));
+
+        let left = InputFrame { movement: LEFT, ..Default::default() };
+        let states = run_headless(states, 1, &mut repeat(left, 1));
+        let direction = states[0].state_object()[0].direction;
+        assert!(direction.x() > 0.0, "{}", direction);
+
+        let states = run_headless(states, 60, &mut repeat(left, 60));
+        let direction = states[0].state_object()[0].direction;
+        assert!((direction.x() + 1.0).abs() < 0.01, "{}", direction);
+    }
+
+    #[test]
+    fn bundled_player_physics_is_valid() {
+        Physics::load(PLAYER_PHYSICS_PATH).unwrap();
+    }
+
+    #[test]
+    fn bundled_hook_spec_is_valid() {
+        HookSpec::load(HOOK_SPEC_PATH).unwrap();
+    }
+
+    #[test]
+    fn slow_frames_catch_up_and_carry_the_leftover_time() {
+        let (steps, leftover) = fixed_steps(0.0, FIXED_TIME_STEP * 3.5);
+        assert_eq!(steps, 3);
+        assert!((leftover - FIXED_TIME_STEP * 0.5).abs() < 1e-6, "{}", leftover);
+
+        let (steps, leftover) = fixed_steps(leftover, FIXED_TIME_STEP * 0.6);
+        assert_eq!(steps, 1);
+        assert!((leftover - FIXED_TIME_STEP * 0.1).abs() < 1e-6, "{}", leftover);
+
+        let (steps, _) = fixed_steps(0.0, FIXED_TIME_STEP * 0.5);
+        assert_eq!(steps, 0);
+    }
+
+    #[test]
+    fn stalled_frame_is_capped() {
+        let (steps, leftover) = fixed_steps(0.0, 10.0);
+
+        assert_eq!(steps, (MAX_FRAME_TIME / FIXED_TIME_STEP).round() as usize);
+        assert!(leftover < FIXED_TIME_STEP);
+    }
+
+    #[test]
+    fn player_stands_still_without_input() {
+        let states = run_headless(player(), 60, &mut NullInput);
+
+        assert_eq!(states[0].state_object()[0].position, Position::new(200.0, 200.0));
+    }
+
+    #[test]
+    fn hook_extends_contracts_and_returns() {
+        let mut input = ScriptedInput::new(vec![InputFrame { shoot: ButtonState::tapped(), ..Default::default() }]);
+        let states = run_headless(player(), 1, &mut input);
+        assert!(matches!(player_state(&states), PlayerStateMachine::Idling(_)));
+        assert!(matches!(main_hook(&states), Some(HookState::Extending(_))));
+
+        let states = run_headless(states, 60 * 4, &mut input);
+        assert!(matches!(main_hook(&states), Some(HookState::Contracting(_))));
+
+        let states = run_headless(states, 60 * 10, &mut input);
+        assert!(matches!(player_state(&states), PlayerStateMachine::Idling(_)));
+        assert!(main_hook(&states).is_none());
+    }
+
+    #[test]
+    fn held_shoot_charges_a_farther_and_faster_throw() {
+        let hold = InputFrame { shoot: ButtonState::held(), ..Default::default() };
+        let mut input = ScriptedInput::new(vec![InputFrame { shoot: ButtonState::pressed(), ..Default::default() }]);
+        let states = run_headless(player(), 1, &mut input);
+        let ticks = (CHARGE_DURATION / FIXED_TIME_STEP) as usize + 1;
+        let states = run_headless(states, ticks, &mut repeat(hold, ticks));
+        let PlayerStateMachine::Charging(state) = player_state(&states) else {
+            panic!("Holding shoot should charge, was {}", player_state(&states));
+        };
+        assert_eq!(state.charge(), 1.0);
+
+        let states = run_headless(states, 1, &mut NullInput);
+        let Some(HookState::Extending(hook)) = main_hook(&states) else {
+            panic!("Releasing shoot should fire, was {}", states[0]);
+        };
+        assert_eq!(hook.max_links(), (HOOK_SPEC.amount_links as f32 * CHARGE_MAX_FACTOR) as usize);
+        assert_eq!(hook.extend_speed().value(), HOOK_SPEC.extend_speed * CHARGE_MAX_FACTOR);
+    }
+
+    #[test]
+    fn extending_hook_can_be_retracted_or_snapped_back() {
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        let retract = InputFrame { retract: ButtonState::pressed(), ..Default::default() };
+        let extending = || run_headless(player(), 5, &mut repeat(shoot, 1));
+
+        let retracted = run_headless(extending(), 1, &mut repeat(retract, 1));
+        let Some(HookState::Contracting(retracted)) = main_hook(&retracted) else {
+            panic!("Retract should pull the hook back, was {}", retracted[0]);
+        };
+        assert_eq!(retracted.contract_speed().value(), HOOK_SPEC.contract_speed);
+
+        let snapped = run_headless(extending(), 1, &mut repeat(shoot, 1));
+        let Some(HookState::Contracting(snapped)) = main_hook(&snapped) else {
+            panic!("Shooting again should snap the hook back, was {}", snapped[0]);
+        };
+        assert!(snapped.contract_speed().value() > HOOK_SPEC.contract_speed);
+    }
+
+    #[test]
+    fn running_while_extending_drags_the_tail_and_keeps_links_spaced() {
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        for movement in [RIGHT, LEFT, DOWN] {
+            let run = InputFrame { movement, ..Default::default() };
+            let states = run_headless(player(), 1, &mut repeat(shoot, 1));
+            let states = run_headless(states, 40, &mut repeat(run, 40));
+
+            let Some(HookState::Extending(hook)) = main_hook(&states) else {
+                panic!("Hook should still be out, was {}", states[0]);
+            };
+            let chain = hook.chain();
+            let position = player_state(&states).body().position;
+            assert_eq!(chain.tail().position(), position);
+            assert_ne!(position, Position::new(200.0, 200.0));
+            let path = chain.path();
+            for (a, b) in path.iter().zip(&path[1..]) {
+                assert!(a.distance(b) <= HOOK_SPEC.link_length + 0.01, "{} to {} in {:?}", a, b, path);
+            }
+            let reach = path[0].distance(&chain.tail().position()) / HOOK_SPEC.link_length;
+            assert!(path.len() - 2 <= reach.ceil() as usize + 1, "Too many links in {:?}", path);
+        }
+    }
+
+    #[test]
+    fn off_hand_hooks_run_alongside_the_main_hook() {
+        let off_hand = InputFrame { shoot_off_hand: ButtonState::tapped(), ..Default::default() };
+        let both = InputFrame { shoot: ButtonState::tapped(), ..off_hand };
+        let mut input = ScriptedInput::new(vec![both, off_hand, InputFrame::default(), off_hand]);
+        let states = run_headless(player(), 10, &mut input);
+
+        let StateMachineEnum::Player(player) = &states[0] else { panic!("First state should be the player") };
+        assert!(matches!(player.main_hook(), Some(HookState::Extending(_))));
+        assert_eq!(player.children().len(), OFF_HAND_HOOKS + 1);
+        assert_eq!(player.hook_heads(0).len(), OFF_HAND_HOOKS + 1);
+        let heads = player.drawable().into_iter().filter(|d| matches!(d.shape, Shape::HookObject(_))).count();
+        assert_eq!(heads, OFF_HAND_HOOKS + 1);
+        let colliders = player.collision_shape();
+        let colliders = colliders.iter().filter(|(part, _)| matches!(part, Part::HookHead { .. })).count();
+        assert_eq!(colliders, OFF_HAND_HOOKS + 1);
+
+        let states = run_headless(states, 60 * 14, &mut input);
+        let StateMachineEnum::Player(player) = &states[0] else { panic!("First state should be the player") };
+        assert!(matches!(player.parent(), PlayerStateMachine::Idling(_)), "{}", player);
+        assert!(player.children().is_empty(), "{}", player);
+    }
+
+    #[test]
+    fn off_hand_hooks_follow_the_running_player() {
+        let off_hand = InputFrame { shoot_off_hand: ButtonState::tapped(), ..Default::default() };
+        let run = InputFrame { movement: DOWN, ..Default::default() };
+        let states = run_headless(player(), 1, &mut repeat(off_hand, 1));
+        let states = run_headless(states, 20, &mut repeat(run, 20));
+
+        let StateMachineEnum::Player(player) = &states[0] else { panic!("First state should be the player") };
+        let [hook] = player.children() else { panic!("One off-hand hook should be out, was {}", player) };
+        assert_eq!(hook.state().chain().tail().position(), player.parent().state_object()[0].position);
+        assert_ne!(player.parent().state_object()[0].position, Position::new(200.0, 200.0));
+    }
+
+    #[test]
+    fn launched_hook_does_not_collide_with_its_player() {
+        let mut states = player();
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+
+        let contacts = step(&mut states, FIXED_TIME_STEP, shoot, &CollisionMasks::default());
+
+        assert!(matches!(main_hook(&states), Some(HookState::Extending(_))));
+        assert!(contacts.is_empty(), "{:?}", contacts);
+    }
+
+    #[test]
+    fn hook_is_shot_toward_the_cursor() {
+        let mut states = player();
+        let behind = Some(Position::new(100.0, 300.0));
+        let shoot = InputFrame { aim: behind, shoot: ButtonState::tapped(), ..Default::default() };
+        let aim = InputFrame { aim: behind, ..Default::default() };
+        let masks = CollisionMasks::default();
+
+        step(&mut states, FIXED_TIME_STEP, shoot, &masks);
+        step(&mut states, FIXED_TIME_STEP, aim, &masks);
+
+        let head = hook_heads(&states)[0];
+        assert!(head.position.x() < 200.0 && head.position.y() > 200.0, "{}", head.position);
+        assert!(player_state(&states).state_object()[0].direction.x() < 0.0);
+    }
+
+    #[test]
+    fn fast_hook_does_not_pass_through_thin_wall() {
+        let wall = StateMachineEnum::Wall(Wall::new(Position::new(300.0, 200.0), 4.0, 100.0));
+        let mut states = vec![init_player(PLAYER_PHYSICS, HOOK_SPEC), wall];
+        let masks = CollisionMasks::default();
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        step(&mut states, FIXED_TIME_STEP, shoot, &masks);
+
+        step(&mut states, 0.5, InputFrame::default(), &masks);
+        step(&mut states, FIXED_TIME_STEP, InputFrame::default(), &masks);
+
+        let Some(HookState::Anchored(hook)) = main_hook(&states) else {
+            panic!("Hook should anchor on the wall, was {}", states[0]);
+        };
+        assert!(matches!(player_state(&states), PlayerStateMachine::Grapled(_)));
+        assert!(hook.anchor().x() < 300.0, "{}", hook.anchor());
+    }
+
+    #[test]
+    fn hook_passes_through_a_wall_its_layer_does_not_collide_with() {
+        let wall = StateMachineEnum::Wall(Wall::new(Position::new(300.0, 200.0), 4.0, 100.0));
+        let mut states = vec![init_player(PLAYER_PHYSICS, HOOK_SPEC), wall];
+        let masks = CollisionMasks::from(CollisionMasksFile::from([(Layer::Hook, vec![Layer::Player, Layer::Item])]));
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        step(&mut states, FIXED_TIME_STEP, shoot, &masks);
+
+        for _ in 0..30 {
+            step(&mut states, FIXED_TIME_STEP, InputFrame::default(), &masks);
+        }
+
+        assert!(matches!(main_hook(&states), Some(HookState::Extending(_))), "{}", states[0]);
+        assert!(hook_heads(&states)[0].position.x() > 302.0, "{}", states[0]);
+    }
+
+    #[test]
+    fn dash_bursts_forward_then_cools_down() {
+        let dash = InputFrame { dash: ButtonState::pressed(), ..Default::default() };
+        let states = run_headless(player(), 1, &mut repeat(dash, 1));
+        assert!(matches!(player_state(&states), PlayerStateMachine::Dashing(_)));
+
+        let states = run_headless(states, 30, &mut NullInput);
+        assert!(matches!(player_state(&states), PlayerStateMachine::Idling(_)));
+        let expected = 200.0 + DASH_SPEED.value() * DASH_DURATION;
+        let position = states[0].state_object()[0].position;
+        assert!((position.x() - expected).abs() < 0.1, "{}", position);
+
+        let states = run_headless(states, 1, &mut repeat(dash, 1));
+        assert!(matches!(player_state(&states), PlayerStateMachine::Idling(_)));
+    }
+
+    #[test]
+    fn dashing_player_cannot_be_touched_while_invulnerable() {
+        let dash = InputFrame { dash: ButtonState::pressed(), ..Default::default() };
+        let touches_body = |states: &mut Vec<StateMachineEnum>| {
+            let item = build(states[0].state_object()[0].position, RIGHT, Magnitude::zero());
+            states.push(StateMachineEnum::Item(ItemStateMachine::Moving(item)));
+            let contacts = collisions(states, &CollisionMasks::default());
+            states.pop();
+            contacts.iter().any(|contact| contact.own.part == Part::PlayerBody)
+        };
+
+        let mut states = run_headless(player(), 1, &mut repeat(dash, 1));
+        assert!(player_state(&states).is_invulnerable());
+        assert!(!touches_body(&mut states));
+
+        let ticks = (DASH_INVULNERABLE_DURATION / FIXED_TIME_STEP).ceil() as usize;
+        let mut states = run_headless(states, ticks, &mut NullInput);
+        assert!(!player_state(&states).is_invulnerable());
+        assert!(touches_body(&mut states));
+    }
+
+    #[test]
+    fn dash_drags_extending_hook() {
+        let shoot_then_dash = vec![
+            InputFrame { shoot: ButtonState::tapped(), ..Default::default() },
+            InputFrame::default(),
+            InputFrame { dash: ButtonState::pressed(), ..Default::default() },
+        ];
+        let states = run_headless(player(), 3, &mut ScriptedInput::new(shoot_then_dash));
+        assert!(matches!(player_state(&states), PlayerStateMachine::Dashing(_)));
+        assert!(matches!(main_hook(&states), Some(HookState::Extending(_))));
+
+        let states = run_headless(states, 15, &mut NullInput);
+        let (PlayerStateMachine::Idling(state), Some(HookState::Extending(hook))) =
+            (player_state(&states), main_hook(&states))
+        else {
+            panic!("Dash should end while the hook is still extending");
+        };
+        assert_eq!(hook.chain().tail().position(), state.position());
+    }
+
+    #[test]
+    fn hook_extending_during_a_dash_anchors_on_a_thin_wall() {
+        let wall = StateMachineEnum::Wall(Wall::new(Position::new(260.0, 200.0), 4.0, 100.0));
+        let shoot_then_dash = vec![
+            InputFrame { shoot: ButtonState::tapped(), ..Default::default() },
+            InputFrame { dash: ButtonState::pressed(), ..Default::default() },
+        ];
+        let mut input = ScriptedInput::new(shoot_then_dash);
+        let mut states = vec![init_player(PLAYER_PHYSICS, HOOK_SPEC), wall];
+        let masks = CollisionMasks::default();
+
+        let mut dashed = false;
+        for _ in 0..(DASH_DURATION / FIXED_TIME_STEP) as usize + 2 {
+            step(&mut states, FIXED_TIME_STEP, input.next_frame(), &masks);
+            dashed |= matches!(player_state(&states), PlayerStateMachine::Dashing(_))
+                && matches!(main_hook(&states), Some(HookState::Extending(_)));
+        }
+
+        assert!(dashed);
+        let Some(HookState::Anchored(hook)) = main_hook(&states) else {
+            panic!("Hook should anchor on the wall during the dash, was {}", states[0]);
+        };
+        assert!(matches!(player_state(&states), PlayerStateMachine::Grapled(_)));
+        assert!(hook.anchor().x() < 260.0, "{}", hook.anchor());
+    }
+
+    #[test]
+    fn hook_anchors_on_wall_and_reels_player_in() {
+        let wall = StateMachineEnum::Wall(Wall::new(Position::new(400.0, 200.0), 40.0, 300.0));
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        let states = run_headless(vec![init_player(PLAYER_PHYSICS, HOOK_SPEC), wall], 40, &mut repeat(shoot, 1));
+        let player = player_state(&states);
+        assert!(matches!(player, PlayerStateMachine::Grapled(_)), "{}", player);
+
+        let states = run_headless(states, 60 * 2, &mut NullInput);
+        assert!(matches!(player_state(&states), PlayerStateMachine::Idling(_)));
+        let position = states[0].state_object()[0].position;
+        assert!(position.x() > 300.0 && position.x() < 380.0, "{}", position);
+        assert_eq!(position.y(), 200.0);
+    }
+
+    #[test]
+    fn hook_catches_item_and_brings_it_back() {
+        let item = build(Position::new(300.0, 200.0), RIGHT, Magnitude::zero());
+        let item = StateMachineEnum::Item(ItemStateMachine::Moving(item));
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        let states = run_headless(vec![init_player(PLAYER_PHYSICS, HOOK_SPEC), item], 13, &mut repeat(shoot, 1));
+        let player = player_state(&states);
+        assert!(matches!(main_hook(&states), Some(HookState::Contracting(_))), "{}", player);
+        assert!(matches!(&states[1], StateMachineEnum::Item(ItemStateMachine::Hooked(_))), "{}", states[1]);
+
+        let states = run_headless(states, 60 * 2, &mut NullInput);
+        assert!(matches!(player_state(&states), PlayerStateMachine::Idling(_)));
+        let StateMachineEnum::Item(item) = &states[1] else {
+            panic!("Second state should be the item");
+        };
+        assert!(item.is_collected(), "{}", item);
+    }
+
+    #[test]
+    fn hooked_item_follows_only_the_hook_that_caught_it() {
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        let retract = InputFrame { retract: ButtonState::pressed(), ..Default::default() };
+        let states = run_headless(player(), 20, &mut repeat(shoot, 1));
+        let mut states = run_headless(states, 1, &mut repeat(retract, 1));
+        let head = hook_heads(&states)[0];
+        let hooked_on = |hook| {
+            let owner = Owner { entity: 0, part: Part::HookHead { hook, catching: true } };
+            let item = build(head.position, RIGHT, Magnitude::zero()).hooked(head.position, owner);
+            StateMachineEnum::Item(ItemStateMachine::Hooked(item))
+        };
+        states.extend([hooked_on(0), hooked_on(1)]);
+
+        let states = run_headless(states, 1, &mut NullInput);
+
+        assert!(matches!(&states[1], StateMachineEnum::Item(ItemStateMachine::Hooked(_))), "{}", states[1]);
+        let StateMachineEnum::Item(ItemStateMachine::Moving(dropped)) = &states[2] else {
+            panic!("Item of a hook that is not out should be dropped, was {}", states[2]);
+        };
+        assert_eq!(dropped.position(), head.position);
+        assert_eq!(dropped.speed().value(), 0.0);
+    }
+
+    #[test]
+    fn rotated_hook_passes_an_item_its_bounds_overlap() {
+        let item = build(Position::new(286.0, 226.0), RIGHT, Magnitude::zero());
+        let item = StateMachineEnum::Item(ItemStateMachine::Moving(item));
+        let mut states = vec![init_player(PLAYER_PHYSICS, HOOK_SPEC), item];
+        let aim = Some(Position::new(400.0, 400.0));
+        let shoot = InputFrame { aim, shoot: ButtonState::tapped(), ..Default::default() };
+        let masks = CollisionMasks::default();
+        step(&mut states, FIXED_TIME_STEP, shoot, &masks);
+
+        let mut bounds_overlapped = false;
+        for _ in 0..30 {
+            step(&mut states, FIXED_TIME_STEP, InputFrame { aim, ..Default::default() }, &masks);
+            let colliders = collision_boxes(&states);
+            let bounds = |part: fn(&Part) -> bool| colliders.iter().find(|c| part(&c.owner.part)).unwrap().bounds;
+            let head = bounds(|part| matches!(part, Part::HookHead { .. }));
+            bounds_overlapped |= head.collision_with(&bounds(|part| *part == Part::Item));
+
+            assert!(matches!(main_hook(&states), Some(HookState::Extending(_))), "{}", states[0]);
+            assert!(matches!(&states[1], StateMachineEnum::Item(ItemStateMachine::Moving(_))), "{}", states[1]);
+        }
+        assert!(bounds_overlapped);
+    }
+
+    #[test]
+    fn item_touching_the_chain_makes_contact() {
+        let shoot = InputFrame { shoot: ButtonState::tapped(), ..Default::default() };
+        let mut states = run_headless(player(), 30, &mut repeat(shoot, 1));
+        let item = build(Position::new(280.0, 200.0), RIGHT, Magnitude::zero());
+        states.push(StateMachineEnum::Item(ItemStateMachine::Moving(item)));
+
+        let contacts = collisions(&states, &CollisionMasks::default());
+
+        assert!(
+            contacts
+                .iter()
+                .any(|contact| matches!(contact.own.part, Part::ChainSegment { .. }) && contact.other.entity == 1),
+            "{:?}",
+            contacts
+        );
+    }
+
+    #[test]
+    fn item_moves_without_input() {
+        let states = run_headless(vec![init_item()], 60, &mut NullInput);
+
+        assert!(states[0].state_object()[0].position.distance(&Position::new(200.0, 200.0)) > 50.0);
+    }
+}
@@ -1,26 +1,39 @@
+use std::cell::RefCell;
 use std::fmt::Display;
 
+use crate::input::InputFrame;
+use crate::state::item::ItemStateMachine;
+use crate::state::state_machine::hook::Dropped;
+use crate::state::player::Player;
+use crate::state::wall::Wall;
 use crate::state::state_machine::{State, item::ItemState};
 
 use super::*;
 
+pub mod hierarchy;
 pub mod hook;
 pub mod item;
 pub mod player;
 pub mod state_machine;
+pub mod wall;
 
-#[derive(Default)]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Default)]
 pub enum StateMachineEnum {
-    Player(PlayerStateMachine),
+    Player(Player),
     Item(ItemStateMachine),
+    Wall(Wall),
+    DroppedHook(Dropped),
     #[default]
     Default,
 }
 impl StateMachineEnum {
-    pub fn update(self) -> Self {
+    pub fn update(self, tick: &Tick) -> Self {
         match self {
-            StateMachineEnum::Player(player_state_enum) => StateMachineEnum::Player(player_state_enum.update()),
-            StateMachineEnum::Item(item_state_enum) => StateMachineEnum::Item(item_state_enum.update()),
+            StateMachineEnum::Player(player_state_enum) => StateMachineEnum::Player(player_state_enum.update(tick)),
+            StateMachineEnum::Item(item_state_enum) => StateMachineEnum::Item(item_state_enum.update(tick)),
+            StateMachineEnum::Wall(wall) => StateMachineEnum::Wall(wall.update(tick)),
+            StateMachineEnum::DroppedHook(hook) => StateMachineEnum::DroppedHook(hook.update(tick)),
             StateMachineEnum::Default => panic!("Default variant should not be used"),
         }
     }
@@ -29,13 +42,15 @@ impl StateMachine for StateMachineEnum {
     fn state_object(&self) -> Vec<StateObject> {
         match self {
             StateMachineEnum::Player(player_state) => player_state.state_object(),
-            StateMachineEnum::Item(item_state) => todo!(),
+            StateMachineEnum::Item(item_state) => item_state.state_object(),
+            StateMachineEnum::Wall(wall) => wall.state_object(),
+            StateMachineEnum::DroppedHook(hook) => hook.state_object(),
             StateMachineEnum::Default => panic!("Default variant should not be used"),
         }
     }
 
-    fn update(self) -> Self {
-        self.update()
+    fn update(self, tick: &Tick) -> Self {
+        self.update(tick)
     }
 }
 impl Draw for StateMachineEnum {
@@ -43,21 +58,31 @@ impl Draw for StateMachineEnum {
         match self {
             StateMachineEnum::Player(player_state) => player_state.drawable(),
             StateMachineEnum::Item(item_state) => item_state.drawable(),
+            StateMachineEnum::Wall(wall) => wall.drawable(),
+            StateMachineEnum::DroppedHook(hook) => hook.drawable(),
             StateMachineEnum::Default => panic!("Default variant should not be used"),
         }
     }
 }
 impl collision::Collision for StateMachineEnum {
-    fn collision_box(&self) -> Vec<collision::CollisionBox> {
+    fn collision_shape(&self) -> Vec<(collision::Part, collision::Triangles)> {
         match self {
-            StateMachineEnum::Player(state) => state.collision_box(),
-            StateMachineEnum::Item(state) => state.collision_box(),
+            StateMachineEnum::Player(state) => state.collision_shape(),
+            StateMachineEnum::Item(state) => state.collision_shape(),
+            StateMachineEnum::Wall(state) => state.collision_shape(),
+            StateMachineEnum::DroppedHook(state) => state.collision_shape(),
             StateMachineEnum::Default => panic!("Default variant should not be used"),
         }
     }
 
-    fn collision_detected(&self, /*other object */) {
-        todo!()
+    fn collision_detected(self, contact: &collision::Contact) -> Self {
+        match self {
+            StateMachineEnum::Player(state) => StateMachineEnum::Player(state.collision_detected(contact)),
+            StateMachineEnum::Item(state) => StateMachineEnum::Item(state.collision_detected(contact)),
+            StateMachineEnum::Wall(state) => StateMachineEnum::Wall(state.collision_detected(contact)),
+            StateMachineEnum::DroppedHook(state) => StateMachineEnum::DroppedHook(state.collision_detected(contact)),
+            StateMachineEnum::Default => panic!("Default variant should not be used"),
+        }
     }
 }
 impl Display for StateMachineEnum {
@@ -65,6 +90,8 @@ impl Display for StateMachineEnum {
         match self {
             StateMachineEnum::Player(state_enum) => write!(f, "{}", state_enum),
             StateMachineEnum::Item(item_state) => write!(f, "{}", item_state),
+            StateMachineEnum::Wall(wall) => write!(f, "{}", wall),
+            StateMachineEnum::DroppedHook(hook) => write!(f, "{}", hook),
             StateMachineEnum::Default => write!(f, "Default"),
         }
     }
@@ -72,13 +99,47 @@ impl Display for StateMachineEnum {
 
 pub trait StateMachine: Display {
     fn state_object(&self) -> Vec<StateObject>;
-    fn update(self) -> Self;
+    fn update(self, tick: &Tick) -> Self;
 }
 
+/// Everything a state machine gets from the outside world during a single simulation step
+#[derive(Debug, Clone, Copy)]
+pub struct Tick<'a> {
+    /// Duration of the step in seconds
+    pub dt: f32,
+    pub input: InputFrame,
+    /// Solid objects, as they were at the start of the step. Hook heads stop at them and chains wrap around them
+    pub obstacles: &'a [collision::CollisionBox],
+    /// Items that can be caught, as they were at the start of the step. Hook heads stop at them, so that they do
+    /// not pass through an item before its contact catches it
+    pub items: &'a [collision::Triangles],
+    /// Hook heads, as they were at the start of the step. Hooked items follow them
+    pub hooks: &'a [hook::HookHead],
+    /// Which layers collide. Hook heads only stop at the obstacles and items they can make contact with
+    pub masks: &'a collision::layers::CollisionMasks,
+    /// State machines left behind during the step. They join the world once every state has been updated
+    pub spawned: &'a RefCell<Vec<StateMachineEnum>>,
+}
+impl Tick<'_> {
+    pub fn spawn(&self, state: StateMachineEnum) {
+        self.spawned.borrow_mut().push(state);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
 pub struct StateObject {
     pub position: Position,
     pub direction: Direction,
 }
+impl StateObject {
+    /// Blends towards `next` by `alpha` (0.0 is `self`, 1.0 is `next`)
+    pub fn interpolate(&self, next: &StateObject, alpha: f32) -> Self {
+        StateObject {
+            position: self.position.lerp(next.position, alpha),
+            direction: self.direction.lerp(next.direction, alpha),
+        }
+    }
+}
 impl<T: State> From<&T> for StateObject {
     fn from(state: &T) -> Self {
         StateObject {
@@ -87,3 +148,25 @@ impl<T: State> From<&T> for StateObject {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolate_blends_position_and_direction() {
+        let previous = StateObject { position: Position::new(0.0, 0.0), direction: RIGHT };
+        let next = StateObject { position: Position::new(10.0, 20.0), direction: DOWN };
+
+        let start = previous.interpolate(&next, 0.0);
+        let halfway = previous.interpolate(&next, 0.5);
+        let end = previous.interpolate(&next, 1.0);
+
+        assert_eq!(start.position, previous.position);
+        assert_eq!(start.direction.value(), previous.direction.value());
+        assert_eq!(halfway.position, Position::new(5.0, 10.0));
+        assert!(halfway.direction.value().abs_diff_eq(Vec2::new(1.0, 1.0).normalize(), 1e-5), "{}", halfway.direction);
+        assert_eq!(end.position, next.position);
+        assert_eq!(end.direction.value(), next.direction.value());
+    }
+}
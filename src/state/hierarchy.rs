@@ -0,0 +1,355 @@
+use std::fmt::Display;
+
+use crate::collision::{Collision, Contact, Part, Triangles};
+use crate::draw::{Draw, Drawable};
+use crate::state::{StateMachine, StateObject, Tick};
+
+/// A state machine that carries child state machines of type `C`
+pub trait Parent<C: Child<Self>>: StateMachine + Sized {
+    /// Children the parent starts during this step, next to the ones it already carries
+    fn spawn_children(self, _children: &[C], _tick: &Tick) -> (Self, Vec<C>) {
+        (self, vec![])
+    }
+    /// Takes over what a child did during the step, e.g. the player being pulled along the chain of an anchored hook
+    fn follow_child(self, _child: &C) -> Self {
+        self
+    }
+    /// Told once for every child that finished during this step, with what it reported when it did
+    fn child_finished(self, _finished: C::Finished, _tick: &Tick) -> Self {
+        self
+    }
+}
+
+/// A state machine carried by a parent of type `P`
+pub trait Child<P>: Display + Sized {
+    /// What the child reports to its parent once it has finished
+    type Finished;
+    /// Takes over what the parent did during the step, e.g. the tail of a hook moving to where the player ran
+    fn follow(self, parent: &P) -> Self;
+    /// `Err` once the child has finished and leaves its parent
+    fn update(self, tick: &Tick) -> Result<Self, Self::Finished>;
+    fn state_object(&self) -> StateObject;
+    fn drawable(&self) -> Vec<Drawable>;
+    /// `id` tells the child apart from its parent's other children. It goes into the parts, so that contacts find
+    /// their way back to the child
+    fn collision_shape(&self, id: usize) -> Vec<(Part, Triangles)>;
+    /// `contact` is seen from the child's side, i.e. `contact.own` is one of its parts
+    fn collision_detected(self, contact: &Contact) -> Self;
+}
+
+/// One parent state machine together with the children it carries.
+/// The parent steps first, then every child follows it and steps on its own
+#[derive(Debug)]
+pub struct Hierarchy<P, C> {
+    parent: P,
+    children: Vec<C>,
+    /// The id of every child, in the same order. A child keeps its id for as long as it is carried, even when
+    /// children before it leave
+    ids: Vec<usize>,
+    next_id: usize,
+}
+impl<P, C> Hierarchy<P, C> {
+    pub fn with_parent(parent: P) -> Self {
+        Hierarchy { parent, children: vec![], ids: vec![], next_id: 0 }
+    }
+    pub fn parent(&self) -> &P {
+        &self.parent
+    }
+    pub fn children(&self) -> &[C] {
+        &self.children
+    }
+    /// The children together with their ids
+    pub fn children_by_id(&self) -> impl Iterator<Item = (usize, &C)> {
+        self.ids.iter().copied().zip(&self.children)
+    }
+}
+impl<P, C> StateMachine for Hierarchy<P, C>
+where
+    P: Parent<C>,
+    C: Child<P>,
+{
+    /// The parent's objects come first, followed by one for every child
+    fn state_object(&self) -> Vec<StateObject> {
+        let mut objects = self.parent.state_object();
+        objects.extend(self.children.iter().map(Child::state_object));
+        objects
+    }
+
+    fn update(self, tick: &Tick) -> Self {
+        let Hierarchy { parent, children, ids, mut next_id } = self;
+        let mut parent = parent.update(tick);
+        let mut remaining = vec![];
+        let mut remaining_ids = vec![];
+        let mut finished = vec![];
+        for (id, child) in ids.into_iter().zip(children) {
+            match child.follow(&parent).update(tick) {
+                Ok(child) => {
+                    remaining.push(child);
+                    remaining_ids.push(id);
+                }
+                Err(end) => finished.push(end),
+            }
+        }
+        for child in &remaining {
+            parent = parent.follow_child(child);
+        }
+        for end in finished {
+            parent = parent.child_finished(end, tick);
+        }
+        let (parent, spawned) = parent.spawn_children(&remaining, tick);
+        for child in spawned {
+            remaining.push(child);
+            remaining_ids.push(next_id);
+            next_id += 1;
+        }
+        Hierarchy { parent, children: remaining, ids: remaining_ids, next_id }
+    }
+}
+impl<P, C> Draw for Hierarchy<P, C>
+where
+    P: Parent<C> + Draw,
+    C: Child<P>,
+{
+    fn drawable(&self) -> Vec<Drawable> {
+        let mut drawables = self.parent.drawable();
+        drawables.extend(self.children.iter().flat_map(Child::drawable));
+        drawables
+    }
+}
+impl<P, C> Collision for Hierarchy<P, C>
+where
+    P: Parent<C> + Collision,
+    C: Child<P>,
+{
+    fn collision_shape(&self) -> Vec<(Part, Triangles)> {
+        let mut shape = self.parent.collision_shape();
+        shape.extend(self.children_by_id().flat_map(|(id, child)| child.collision_shape(id)));
+        shape
+    }
+
+    /// Goes to the child the touched part belongs to, or to the parent for its own parts
+    fn collision_detected(self, contact: &Contact) -> Self {
+        let Hierarchy { parent, children, ids, next_id } = self;
+        match contact.own.part.child() {
+            Some(touched) => Hierarchy {
+                parent,
+                children: ids
+                    .iter()
+                    .zip(children)
+                    .map(|(id, child)| if *id == touched { child.collision_detected(contact) } else { child })
+                    .collect(),
+                ids,
+                next_id,
+            },
+            None => Hierarchy {
+                parent: parent.collision_detected(contact),
+                children,
+                ids,
+                next_id,
+            },
+        }
+    }
+}
+impl<P: Display, C: Display> Display for Hierarchy<P, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.parent)?;
+        for child in &self.children {
+            write!(f, "\n\tChild {}", child)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::collision::layers::CollisionMasks;
+    use crate::collision::{CollisionBox, Owner};
+    use crate::input::InputFrame;
+    use crate::model::*;
+    use macroquad::math::Vec2;
+
+    /// Moves one unit to the right every step and keeps track of what its children told it
+    #[derive(Debug, Default)]
+    struct Body {
+        x: f32,
+        to_spawn: Vec<u32>,
+        followed: Vec<u32>,
+        finished: Vec<u32>,
+        touched: bool,
+    }
+    /// Sits where its parent is, and finishes once it has no steps left
+    #[derive(Debug)]
+    struct Limb {
+        id: u32,
+        x: f32,
+        steps_left: u32,
+        touched: bool,
+    }
+    fn limb(id: u32, steps_left: u32) -> Limb {
+        Limb { id, x: 0.0, steps_left, touched: false }
+    }
+    fn carrying(parent: Body, children: Vec<Limb>) -> Hierarchy<Body, Limb> {
+        let ids = (0..children.len()).collect();
+        Hierarchy { parent, next_id: children.len(), children, ids }
+    }
+
+    impl Display for Body {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Body {}", self.x)
+        }
+    }
+    impl StateMachine for Body {
+        fn state_object(&self) -> Vec<StateObject> {
+            vec![StateObject { position: Position::new(self.x, 0.0), direction: RIGHT }]
+        }
+        fn update(self, _: &Tick) -> Self {
+            Body { x: self.x + 1.0, ..self }
+        }
+    }
+    impl Draw for Body {
+        fn drawable(&self) -> Vec<Drawable> {
+            vec![]
+        }
+    }
+    impl Collision for Body {
+        fn collision_shape(&self) -> Vec<(Part, Triangles)> {
+            vec![]
+        }
+        fn collision_detected(self, _: &Contact) -> Self {
+            Body { touched: true, ..self }
+        }
+    }
+    impl Parent<Limb> for Body {
+        fn spawn_children(mut self, _: &[Limb], _: &Tick) -> (Self, Vec<Limb>) {
+            let spawned = self.to_spawn.drain(..).map(|id| limb(id, 10)).collect();
+            (self, spawned)
+        }
+        fn follow_child(mut self, child: &Limb) -> Self {
+            self.followed.push(child.id);
+            self
+        }
+        fn child_finished(mut self, id: u32, _: &Tick) -> Self {
+            self.finished.push(id);
+            self
+        }
+    }
+
+    impl Display for Limb {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Limb {}", self.id)
+        }
+    }
+    impl Child<Body> for Limb {
+        type Finished = u32;
+        fn follow(self, parent: &Body) -> Self {
+            Limb { x: parent.x, ..self }
+        }
+        fn update(self, _: &Tick) -> Result<Self, u32> {
+            match self.steps_left {
+                0 => Err(self.id),
+                steps_left => Ok(Limb { steps_left: steps_left - 1, ..self }),
+            }
+        }
+        fn state_object(&self) -> StateObject {
+            StateObject { position: Position::new(self.x, 0.0), direction: RIGHT }
+        }
+        fn drawable(&self) -> Vec<Drawable> {
+            vec![]
+        }
+        fn collision_shape(&self, index: usize) -> Vec<(Part, Triangles)> {
+            vec![(Part::HookHead { hook: index, catching: false }, Triangles(vec![]))]
+        }
+        fn collision_detected(self, _: &Contact) -> Self {
+            Limb { touched: true, ..self }
+        }
+    }
+
+    fn step(hierarchy: Hierarchy<Body, Limb>) -> Hierarchy<Body, Limb> {
+        let spawned = RefCell::new(vec![]);
+        let tick = Tick {
+            dt: 1.0 / 60.0,
+            input: InputFrame::default(),
+            obstacles: &[],
+            items: &[],
+            hooks: &[],
+            masks: &CollisionMasks::default(),
+            spawned: &spawned,
+        };
+        hierarchy.update(&tick)
+    }
+
+    fn contact_on(part: Part) -> Contact {
+        Contact {
+            own: Owner { entity: 0, part },
+            other: Owner { entity: 1, part: Part::Wall },
+            overlap: CollisionBox { lower: Vec2::ZERO, upper: Vec2::ZERO },
+            depth: 0.0,
+            normal: Vec2::X,
+        }
+    }
+
+    fn ids(hierarchy: &Hierarchy<Body, Limb>) -> Vec<u32> {
+        hierarchy.children().iter().map(|child| child.id).collect()
+    }
+
+    #[test]
+    fn children_follow_the_parent_after_it_stepped() {
+        let hierarchy = carrying(Body::default(), vec![limb(1, 10), limb(2, 10)]);
+
+        let hierarchy = step(hierarchy);
+
+        assert!(hierarchy.children().iter().all(|child| child.x == 1.0));
+        assert_eq!(hierarchy.parent().followed, vec![1, 2]);
+    }
+
+    #[test]
+    fn finished_children_leave_and_are_reported_to_the_parent() {
+        let hierarchy = carrying(Body::default(), vec![limb(1, 0), limb(2, 10)]);
+
+        let hierarchy = step(hierarchy);
+
+        assert_eq!(ids(&hierarchy), vec![2]);
+        assert_eq!(hierarchy.parent().followed, vec![2]);
+        assert_eq!(hierarchy.parent().finished, vec![1]);
+    }
+
+    #[test]
+    fn spawned_children_are_added_after_the_others_in_order() {
+        let parent = Body { to_spawn: vec![2, 3], ..Default::default() };
+        let hierarchy = carrying(parent, vec![limb(1, 10)]);
+
+        let hierarchy = step(hierarchy);
+
+        assert_eq!(ids(&hierarchy), vec![1, 2, 3]);
+        assert_eq!(hierarchy.state_object().len(), 4);
+    }
+
+    #[test]
+    fn contacts_go_to_the_child_the_touched_part_belongs_to() {
+        let hierarchy = carrying(Body::default(), vec![limb(1, 10), limb(2, 10)]);
+        let part = hierarchy.collision_shape()[1].0;
+
+        let hierarchy = hierarchy.collision_detected(&contact_on(part));
+        let touched: Vec<bool> = hierarchy.children().iter().map(|child| child.touched).collect();
+        assert_eq!(touched, vec![false, true]);
+        assert!(!hierarchy.parent().touched);
+
+        let hierarchy = hierarchy.collision_detected(&contact_on(Part::PlayerBody));
+        assert!(hierarchy.parent().touched);
+    }
+
+    #[test]
+    fn contacts_still_find_a_child_after_an_earlier_one_left() {
+        let hierarchy = carrying(Body::default(), vec![limb(1, 0), limb(2, 10)]);
+        let part = hierarchy.collision_shape()[1].0;
+
+        let hierarchy = step(hierarchy).collision_detected(&contact_on(part));
+
+        assert_eq!(ids(&hierarchy), vec![2]);
+        assert!(hierarchy.children()[0].touched);
+        assert_eq!(hierarchy.collision_shape()[0].0, part);
+    }
+}
@@ -1,11 +1,30 @@
+/// Used when the hook spec cannot be read
+pub const HOOK_SPEC: HookSpec = HookSpec {
+    amount_links: HOOK_AMOUNT_LINKS,
+    link_length: HOOK_LINK_LENGTH,
+    extend_speed: HOOK_EXTENDING_SPEED.value(),
+    contract_speed: HOOK_CONTRACTING_SPEED.value(),
+    reel_speed: HOOK_REEL_SPEED.value(),
+    strength: HOOK_CHAIN_STRENGTH,
+    physics: ChainPhysics::Kinematic,
+};
+pub const HOOK_SPEC_PATH: &str = "assets/config/hook.toml";
 
 use std::fmt::Display;
 
+use itertools::Itertools;
+
 use crate::draw::graphics::hook_graphics::{HOOK_LINK, HOOK_LINK_VERTEX};
+use crate::collision::{Collision, CollisionBox, Contact, Owner, Part, Triangles};
+use crate::draw::graphics::Shape;
+use crate::draw::graphics::hook_graphics::HOOK_GRAPHICS;
 use crate::draw::{Draw, Drawable};
 use crate::model::*;
-use crate::state::{StateMachine, StateObject};
-use crate::state::state_machine::hook::{Chain, Contracting, Extending, build};
+use crate::state::{StateMachine, StateObject, Tick, state_machine::State};
+use crate::state::state_machine::hook::{Chain, ChainPhysics, Contracting, Dropped, Extending, HookSpec};
+use crate::state::state_machine::{
+    HOOK_AMOUNT_LINKS, HOOK_CHAIN_STRENGTH, HOOK_CONTRACTING_SPEED, HOOK_EXTENDING_SPEED, HOOK_LINK_LENGTH, HOOK_REEL_SPEED,
+};
 use crate::util::name_of_type;
 
 #[derive(Debug)]
@@ -25,32 +44,119 @@ impl Display for HookState {
     }
 }
 
+/// Snapshot of a hook head, as seen by other state machines during a step
+#[derive(Debug, Clone, Copy)]
+pub struct HookHead {
+    /// The head's part, as it shows up in contacts
+    pub owner: Owner,
+    pub position: Position,
+    pub direction: Direction,
+}
+impl HookHead {
+    pub fn new<T: State>(owner: Owner, hook: &T) -> Self {
+        HookHead {
+            owner,
+            position: hook.position(),
+            direction: hook.direction(),
+        }
+    }
+}
+
+impl StateMachine for Dropped {
+    fn state_object(&self) -> Vec<StateObject> {
+        vec![StateObject {
+            position: self.position(),
+            direction: self.direction(),
+        }]
+    }
+
+    fn update(self, _: &Tick) -> Self {
+        self
+    }
+}
+impl Draw for Dropped {
+    fn drawable(&self) -> Vec<Drawable> {
+        vec![Drawable {
+            state: self.state_object()[0],
+            shape: Shape::HookObject(HOOK_GRAPHICS),
+        }]
+    }
+}
+impl Collision for Dropped {
+    fn collision_shape(&self) -> Vec<(Part, Triangles)> {
+        let head = HOOK_GRAPHICS.model.rotate(self.direction()).translate(self.position());
+        vec![(Part::DroppedHook, Self::triangles(head))]
+    }
+
+    /// Nothing moves a dropped hook
+    fn collision_detected(self, _: &Contact) -> Self {
+        self
+    }
+}
+impl Display for Dropped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", name_of_type(self), self.position())
+    }
+}
+
+/// Head and chain of a hook
+pub fn hook_as_drawables<T: State>(hook: &T, chain: &Chain) -> Vec<Drawable> {
+    let mut drawables = vec![Drawable {
+        state: hook.into(),
+        shape: Shape::HookObject(HOOK_GRAPHICS),
+    }];
+    drawables.append(&mut hook_chain_as_drawables(chain));
+    drawables
+}
+
+/// Head and chain of a hook. `id` tells the hook apart from the others its player has out, and a `catching` head
+/// catches the items it touches
+pub fn hook_as_colliders<T: State>(id: usize, catching: bool, hook: &T, chain: &Chain) -> Vec<(Part, Triangles)> {
+    let head = HOOK_GRAPHICS.model.rotate(hook.direction()).translate(hook.position());
+    let mut colliders = vec![(Part::HookHead { hook: id, catching }, Triangles::from(head))];
+    colliders.append(&mut hook_chain_as_colliders(id, chain));
+    colliders
+}
+
+/// The chain is drawn along its path, so it bends around the corners it is wrapped on
 pub fn hook_chain_as_drawables(chain: &Chain) -> Vec<Drawable> {
     let mut drawables: Vec<Drawable> = vec![];
     let mut link_shape = HOOK_LINK;
-    let mut it = chain.chain().iter_full();
-    let it_clone = it.clone();
-    let mut prev = it.next().unwrap();
-    for link in it_clone.skip(1) {
+    let path = chain.path();
+    for (prev, link) in path.iter().tuple_windows() {
         link_shape.length = link.distance(prev);
         drawables.push(Drawable {
             state: StateObject {
-                position: link.position(),
-                direction: link.direction(prev),
+                position: *link,
+                direction: link.direction_to(prev),
             },
             shape: link_shape.into(),
         });
-        prev = link;
     }
 
-    for link in it {
+    for link in path.into_iter().skip(1) {
         drawables.push(Drawable {
             state: StateObject {
-                position: link.position(),
+                position: link,
                 direction: Direction::default(),
             },
             shape: HOOK_LINK_VERTEX.into(),
         });
     }
     drawables
-}
\ No newline at end of file
+}
+
+/// One capsule per segment of the path, as thick as the drawn chain. Segments end at links and at wrap corners
+pub fn hook_chain_as_colliders(index: usize, chain: &Chain) -> Vec<(Part, Triangles)> {
+    let radius = HOOK_LINK.thickness / 2.0;
+    chain
+        .path()
+        .iter()
+        .tuple_windows()
+        .enumerate()
+        .map(|(segment, (a, b))| {
+            let part = Part::ChainSegment { hook: index, segment };
+            (part, Triangles::capsule(a.value(), b.value(), radius))
+        })
+        .collect()
+}
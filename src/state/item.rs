@@ -1,16 +1,17 @@
 use std::{fmt::Display, vec};
 
 use crate::{
-    collision,
+    collision::{self, Contact, Part, Triangles},
     draw::{
         Draw, Drawable,
         graphics::{Shape, item_graphics::ITEM_GRAPHICS},
     },
+    model::Position,
     state::{
-        StateMachine,
+        StateMachine, Tick,
         state_machine::{
             State,
-            item::{Hooked, ItemState, Moving},
+            item::{Collected, Hooked, ItemState, Moving},
         },
     },
     util::name_of_type,
@@ -20,6 +21,19 @@ use crate::{
 pub enum ItemStateMachine {
     Moving(Moving),
     Hooked(Hooked),
+    Collected(Collected),
+}
+impl ItemStateMachine {
+    /// Only free moving items can be caught by a hook, so only they stop its head
+    pub fn catchable_triangles(&self) -> Option<Triangles> {
+        match self {
+            ItemStateMachine::Moving(moving) => Some(item_triangles(moving)),
+            _ => None,
+        }
+    }
+    pub fn is_collected(&self) -> bool {
+        matches!(self, ItemStateMachine::Collected(_))
+    }
 }
 
 impl From<ItemState> for ItemStateMachine {
@@ -27,6 +41,7 @@ impl From<ItemState> for ItemStateMachine {
         match value {
             ItemState::Moving(moving) => ItemStateMachine::Moving(moving),
             ItemState::Hooked(hooked) => ItemStateMachine::Hooked(hooked),
+            ItemState::Collected(collected) => ItemStateMachine::Collected(collected),
         }
     }
 }
@@ -35,13 +50,15 @@ impl StateMachine for ItemStateMachine {
         match self {
             ItemStateMachine::Moving(moving) => vec![moving.into()],
             ItemStateMachine::Hooked(hooked) => vec![hooked.into()],
+            ItemStateMachine::Collected(_) => vec![],
         }
     }
 
-    fn update(self) -> Self {
+    fn update(self, tick: &Tick) -> Self {
         match self {
-            ItemStateMachine::Moving(moving) => moving.update().into(),
-            ItemStateMachine::Hooked(hooked) => hooked.update().into(),
+            ItemStateMachine::Moving(moving) => moving.update(tick).into(),
+            ItemStateMachine::Hooked(hooked) => hooked.update(tick).into(),
+            ItemStateMachine::Collected(collected) => collected.update(tick).into(),
         }
     }
 }
@@ -57,37 +74,47 @@ impl Draw for ItemStateMachine {
                 state: hooked.into(),
                 shape: Shape::ItemObject(ITEM_GRAPHICS),
             }],
+            ItemStateMachine::Collected(_) => vec![],
         }
     }
 }
 impl collision::Collision for ItemStateMachine {
-    fn collision_box(&self) -> Vec<collision::CollisionBox> {
+    fn collision_shape(&self) -> Vec<(Part, Triangles)> {
         match self {
-            ItemStateMachine::Moving(state) => vec![Self::bounds(
-                ITEM_GRAPHICS
-                    .model
-                    .rotate(state.direction())
-                    .translate(state.position()),
-            )],
-            ItemStateMachine::Hooked(state) => vec![Self::bounds(
-                ITEM_GRAPHICS
-                    .model
-                    .rotate(state.direction())
-                    .translate(state.position()),
-            )],
+            ItemStateMachine::Moving(state) => vec![(Part::Item, item_triangles(state))],
+            ItemStateMachine::Hooked(state) => vec![(Part::Pickup, item_triangles(state))],
+            ItemStateMachine::Collected(_) => vec![],
         }
     }
 
-    fn collision_detected(&self /*other object */) {
-        todo!()
+    /// A free moving item is caught by the head of an extending hook that touches it, and a hooked one is collected
+    /// once it touches the player
+    fn collision_detected(self, contact: &Contact) -> Self {
+        match (self, contact.other.part) {
+            (ItemStateMachine::Moving(state), Part::HookHead { catching: true, .. }) => {
+                let at = (contact.overlap.lower + contact.overlap.upper) / 2.0;
+                ItemStateMachine::Hooked(state.hooked(Position::from_vec(at), contact.other))
+            }
+            (ItemStateMachine::Hooked(state), Part::PlayerBody) => ItemStateMachine::Collected(state.collected()),
+            (state, _) => state,
+        }
     }
 }
+fn item_triangles<T: State>(state: &T) -> Triangles {
+    <ItemStateMachine as collision::Collision>::triangles(
+        ITEM_GRAPHICS
+            .model
+            .rotate(state.direction())
+            .translate(state.position()),
+    )
+}
 impl Display for ItemStateMachine {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{} ", name_of_type(self));
         match self {
             ItemStateMachine::Moving(moving) => write!(f, "{}", moving),
             ItemStateMachine::Hooked(hooked) => write!(f, "{}", hooked),
+            ItemStateMachine::Collected(collected) => write!(f, "{}", collected),
         }
     }
 }
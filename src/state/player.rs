@@ -1,24 +1,36 @@
-pub const PLAYER_SPEED: Magnitude = Magnitude::new(2.5);
+/// Used when the physics config cannot be read
+pub const PLAYER_PHYSICS: Physics = Physics {
+    min_speed: 5.0,
+    max_speed: 150.0,
+    acceleration: 900.0,
+    deceleration: 1200.0,
+    turn_rate: 540.0,
+};
+pub const PLAYER_PHYSICS_PATH: &str = "assets/config/player_physics.toml";
 
 use std::fmt::Display;
 use std::vec;
 
 use super::StateMachine;
 use crate::collision;
-use crate::collision::CollisionBox;
+use crate::collision::{Contact, Owner, Part, Triangles};
 use crate::draw::Draw;
 use crate::draw::Drawable;
-use crate::draw::graphics::Shape;
+use crate::draw::graphics::{Radius, Ring, Shape};
 use crate::draw::graphics::hook_graphics::HOOK_GRAPHICS;
 use crate::draw::graphics::player_graphics::*;
 use crate::model::*;
-use crate::state::hook::hook_chain_as_drawables;
+use crate::state::hierarchy::{Child, Hierarchy, Parent};
+use crate::state::hook::{HookHead, hook_as_colliders, hook_as_drawables};
 use crate::state::{
-    StateObject,
+    StateObject, Tick,
     state_machine::{
         State,
-        hook::{Contracting, Extending},
-        player::{Idling, ParentChild, PlayerState, build},
+        hook::{HookSpec, HookState},
+        player::{
+            Charging, Dashing, Grapled, Hand, HookEnd, Idling, PlayerState, ThrownHook, build, throw_off_hand,
+            update_hook,
+        },
     },
 };
 use crate::util::*;
@@ -46,123 +58,192 @@ where
     }
 }
 
+/// The player's body, carrying the hooks it has thrown: the main hook and those from its off hand
+pub type Player = Hierarchy<PlayerStateMachine, ThrownHook>;
+impl Player {
+    pub fn new(position: Position, direction: Direction, physics: Physics, hook: HookSpec) -> Self {
+        Hierarchy::with_parent(PlayerStateMachine::new(position, direction, physics, hook))
+    }
+    /// The heads of all hooks that are out and free to move. `entity` is where the player sits in the world
+    pub fn hook_heads(&self, entity: usize) -> Vec<HookHead> {
+        self.children_by_id()
+            .filter_map(|(id, hook)| {
+                let owner = |catching| Owner { entity, part: Part::HookHead { hook: id, catching } };
+                match hook.state() {
+                    HookState::Extending(state) => Some(HookHead::new(owner(true), state)),
+                    HookState::Contracting(state) => Some(HookHead::new(owner(false), state)),
+                    HookState::Anchored(_) => None,
+                }
+            })
+            .collect()
+    }
+    /// The main hook, if it is out
+    pub fn main_hook(&self) -> Option<&HookState> {
+        self.children()
+            .iter()
+            .find(|hook| hook.hand() == Hand::Main)
+            .map(ThrownHook::state)
+    }
+}
+impl Parent<ThrownHook> for PlayerStateMachine {
+    /// Throws the main hook once a charge has been released, and an off-hand hook when asked to
+    fn spawn_children(self, children: &[ThrownHook], tick: &Tick) -> (Self, Vec<ThrownHook>) {
+        let StateObject { position, direction } = self.body();
+        let off_hand = children.iter().filter(|hook| hook.hand() == Hand::Off).count();
+        let off_hand = throw_off_hand(self.hook_spec(), position, direction, off_hand, tick);
+        let (state, main_hand) = match self {
+            PlayerStateMachine::Idling(state) => {
+                let (state, hook) = state.let_go();
+                (PlayerStateMachine::Idling(state), hook)
+            }
+            state => (state, None),
+        };
+        (state, main_hand.into_iter().chain(off_hand).collect())
+    }
+
+    fn follow_child(self, child: &ThrownHook) -> Self {
+        PlayerState::from(self).follow_hook(child).into()
+    }
+
+    fn child_finished(self, finished: HookEnd, _: &Tick) -> Self {
+        PlayerState::from(self).hook_finished(finished).into()
+    }
+}
+impl Child<PlayerStateMachine> for ThrownHook {
+    type Finished = HookEnd;
+
+    fn follow(self, parent: &PlayerStateMachine) -> Self {
+        self.follow_tail(parent.body().position)
+    }
+
+    fn update(self, tick: &Tick) -> Result<Self, HookEnd> {
+        update_hook(self, tick)
+    }
+
+    fn state_object(&self) -> StateObject {
+        self.state().into()
+    }
+
+    fn drawable(&self) -> Vec<Drawable> {
+        hook_as_drawables(self.state(), self.state().chain())
+    }
+
+    fn collision_shape(&self, id: usize) -> Vec<(Part, Triangles)> {
+        let catching = matches!(self.state(), HookState::Extending(_));
+        hook_as_colliders(id, catching, self.state(), self.state().chain())
+    }
+
+    /// What the head touches decides whether the hook grapples or catches during the next update
+    fn collision_detected(self, contact: &Contact) -> Self {
+        match contact.own.part {
+            Part::HookHead { .. } => self.head_touched(contact.other.part),
+            _ => self,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum PlayerStateMachine {
     Idling(Idling),
-    ParentChildIdlingExtending(ParentChild<Idling, Extending>),
-    ParentChildIdlingContracting(ParentChild<Idling, Contracting>),
+    Charging(Charging),
+    Dashing(Dashing),
+    Grapled(Grapled),
 }
 
 impl PlayerStateMachine {
-    pub fn new(position: Position, direction: Direction, speed: Magnitude) -> Self {
-        Self::Idling(build(position, direction, speed))
+    pub fn new(position: Position, direction: Direction, physics: Physics, hook: HookSpec) -> Self {
+        Self::Idling(build(position, direction, physics, hook))
+    }
+    /// Where the body is, turned the way the player faces. That is where it throws hooks, which is not necessarily
+    /// where it moves
+    pub fn body(&self) -> StateObject {
+        let (position, direction) = match self {
+            PlayerStateMachine::Idling(state) => (state.position(), state.facing()),
+            PlayerStateMachine::Charging(state) => (state.position(), state.facing()),
+            PlayerStateMachine::Dashing(state) => (state.position(), state.direction()),
+            PlayerStateMachine::Grapled(state) => (state.position(), state.direction()),
+        };
+        StateObject { position, direction }
+    }
+    /// The hook the player throws
+    pub fn hook_spec(&self) -> HookSpec {
+        match self {
+            PlayerStateMachine::Idling(state) => state.hook(),
+            PlayerStateMachine::Charging(state) => state.hook(),
+            PlayerStateMachine::Dashing(state) => state.hook(),
+            PlayerStateMachine::Grapled(state) => state.hook(),
+        }
+    }
+    pub fn is_invulnerable(&self) -> bool {
+        match self {
+            PlayerStateMachine::Dashing(state) => state.is_invulnerable(),
+            _ => false,
+        }
     }
 }
 impl StateMachine for PlayerStateMachine {
     fn state_object(&self) -> Vec<super::StateObject> {
-        match self {
-            PlayerStateMachine::Idling(state) => vec![state.into()],
-            PlayerStateMachine::ParentChildIdlingExtending(state) => vec![state.parent().into(), state.child().into()],
-            PlayerStateMachine::ParentChildIdlingContracting(state) => {
-                vec![state.parent().into(), state.child().into()]
-            }
-        }
+        vec![self.body()]
     }
 
-    fn update(self) -> Self {
+    fn update(self, tick: &Tick) -> Self {
         match self {
-            PlayerStateMachine::Idling(state) => state.update().into(),
-            PlayerStateMachine::ParentChildIdlingExtending(state) => state.update().into(),
-            PlayerStateMachine::ParentChildIdlingContracting(state) => state.update().into(),
+            PlayerStateMachine::Idling(state) => state.update(tick).into(),
+            PlayerStateMachine::Charging(state) => state.update(tick).into(),
+            PlayerStateMachine::Dashing(state) => state.update(tick).into(),
+            PlayerStateMachine::Grapled(state) => state.update(tick).into(),
         }
     }
 }
 impl Draw for PlayerStateMachine {
     fn drawable(&self) -> Vec<Drawable> {
+        let body = self.body();
         match self {
-            PlayerStateMachine::Idling(state) => {
+            PlayerStateMachine::Idling(_) => {
                 vec![Drawable {
-                    state: state.into(),
+                    state: body,
                     shape: Shape::PlayerObject(PLAYER_ANIMATION.current_frame()), //todo choose shape based on player speed
                 }]
             }
-            PlayerStateMachine::ParentChildIdlingExtending(state) => {
-                let mut vec = vec![
+            PlayerStateMachine::Charging(state) => {
+                let ring = Ring {
+                    radius: Radius(CHARGE_RING.radius.0 * state.charge()),
+                    ..CHARGE_RING
+                };
+                vec![
                     Drawable {
-                        state: state.parent().into(),
+                        state: body,
                         shape: Shape::PlayerObject(PLAYER_GRAPHICS),
                     },
                     Drawable {
-                        state: state.child().into(),
-                        shape: Shape::HookObject(HOOK_GRAPHICS),
+                        state: body,
+                        shape: ring.into(),
                     },
-                ];
-                vec.append(&mut hook_chain_as_drawables(state.child().chain()));
-                vec
+                ]
             }
-            PlayerStateMachine::ParentChildIdlingContracting(state) => {
-                let mut vec = vec![
-                    Drawable {
-                        state: state.parent().into(),
-                        shape: Shape::PlayerObject(PLAYER_GRAPHICS),
-                    },
-                    Drawable {
-                        state: state.child().into(),
-                        shape: Shape::HookObject(HOOK_GRAPHICS),
-                    },
-                ];
-                vec.append(&mut hook_chain_as_drawables(state.child().chain()));
-                vec
+            PlayerStateMachine::Dashing(_) | PlayerStateMachine::Grapled(_) => {
+                vec![Drawable {
+                    state: body,
+                    shape: Shape::PlayerObject(PLAYER_GRAPHICS),
+                }]
             }
         }
     }
 }
 impl collision::Collision for PlayerStateMachine {
-    fn collision_box(&self) -> Vec<collision::CollisionBox> {
-        match self {
-            PlayerStateMachine::Idling(state) => {
-                let position = state.position();
-                let direction = state.direction();
-                let object = PLAYER_GRAPHICS;
-                vec![Self::bounds(object.model.rotate(direction).translate(position))]
-            }
-            PlayerStateMachine::ParentChildIdlingExtending(state) => {
-                vec![
-                    Self::bounds(
-                        PLAYER_GRAPHICS
-                            .model
-                            .rotate(state.direction())
-                            .translate(state.position()),
-                    ),
-                    Self::bounds(
-                        HOOK_GRAPHICS
-                            .model
-                            .rotate(state.child().direction())
-                            .translate(state.child().position()),
-                    ),
-                ]
-            }
-            PlayerStateMachine::ParentChildIdlingContracting(state) => {
-                vec![
-                    Self::bounds(
-                        PLAYER_GRAPHICS
-                            .model
-                            .rotate(state.direction())
-                            .translate(state.position()),
-                    ),
-                    Self::bounds(
-                        HOOK_GRAPHICS
-                            .model
-                            .rotate(state.child().direction())
-                            .translate(state.child().position()),
-                    ),
-                ]
-            }
+    /// The body is left out while the player is invulnerable, so nothing can touch it
+    fn collision_shape(&self) -> Vec<(Part, Triangles)> {
+        let StateObject { position, direction } = self.body();
+        if self.is_invulnerable() {
+            return vec![];
         }
+        let body = PLAYER_GRAPHICS.model.rotate(direction).translate(position);
+        vec![(Part::PlayerBody, Self::triangles(body))]
     }
 
-    fn collision_detected(&self /*other object */) {
-        todo!()
+    /// Nothing touching the body affects the player yet
+    fn collision_detected(self, _: &Contact) -> Self {
+        self
     }
 }
 
@@ -171,8 +252,9 @@ impl Display for PlayerStateMachine {
         write!(f, "{} ", name_of_type(self));
         match self {
             PlayerStateMachine::Idling(state) => write!(f, "{}", state),
-            PlayerStateMachine::ParentChildIdlingExtending(state) => write!(f, "{}", state),
-            PlayerStateMachine::ParentChildIdlingContracting(state) => write!(f, "{}", state),
+            PlayerStateMachine::Charging(state) => write!(f, "{}", state),
+            PlayerStateMachine::Dashing(state) => write!(f, "{}", state),
+            PlayerStateMachine::Grapled(state) => write!(f, "{}", state),
         }
     }
 }
@@ -181,8 +263,19 @@ impl From<PlayerState> for PlayerStateMachine {
     fn from(value: PlayerState) -> Self {
         match value {
             PlayerState::Idling(state) => PlayerStateMachine::Idling(state),
-            PlayerState::ParentChildIdlingExtending(state) => PlayerStateMachine::ParentChildIdlingExtending(state),
-            PlayerState::ParentChildIdlingContracting(state) => PlayerStateMachine::ParentChildIdlingContracting(state),
+            PlayerState::Charging(state) => PlayerStateMachine::Charging(state),
+            PlayerState::Dashing(state) => PlayerStateMachine::Dashing(state),
+            PlayerState::Grapled(state) => PlayerStateMachine::Grapled(state),
+        }
+    }
+}
+impl From<PlayerStateMachine> for PlayerState {
+    fn from(value: PlayerStateMachine) -> Self {
+        match value {
+            PlayerStateMachine::Idling(state) => PlayerState::Idling(state),
+            PlayerStateMachine::Charging(state) => PlayerState::Charging(state),
+            PlayerStateMachine::Dashing(state) => PlayerState::Dashing(state),
+            PlayerStateMachine::Grapled(state) => PlayerState::Grapled(state),
         }
     }
 }
@@ -6,8 +6,10 @@ use hook::*;
 use player::*;
 use item::*;
 
+use crate::collision::CollisionBox;
 use crate::input::*;
 use crate::model::*;
+use crate::state::{StateMachineEnum, Tick};
 use crate::util::name_of_type;
 
 pub mod player;
@@ -17,19 +19,57 @@ pub mod action;
 
 pub const HOOK_AMOUNT_LINKS: usize = 40;
 pub const HOOK_LINK_LENGTH: f32 = 20.0;
-pub const HOOK_EXTENDING_SPEED: Magnitude = Magnitude::new(5.5);
-pub const HOOK_CONTRACTING_SPEED: Magnitude = Magnitude::new(2.5);
+pub const HOOK_EXTENDING_SPEED: Magnitude = Magnitude::new(330.0);
+pub const HOOK_CONTRACTING_SPEED: Magnitude = Magnitude::new(150.0);
 // pub const HOOK_CONTRACTING_SPEED: Magnitude = Magnitude::new(0.0);
 pub const HOOK_CONTRACTING_HIST_LENGTH: usize = 50;
 pub const HOOK_DIST_END_CONTRACT: f32 = 10.0;
 pub const HOOK_CHAIN_PROJECTION_FACTOR: f32 = 0.1;
 pub const HOOK_LINK_DIST_TREAT_AS_ZERO: f32 = 1.0;
+pub const HOOK_REEL_SPEED: Magnitude = Magnitude::new(400.0);
+/// How many times faster than its contract speed a hook snaps back when shoot is pressed while it is out
+pub const HOOK_SNAP_BACK_FACTOR: f32 = 3.0;
+/// How far the hook head goes into whatever it hits, so that the hit registers as an overlap
+pub const HOOK_SWEEP_SKIN: f32 = 0.5;
+/// How far from the anchor the player lands after grappling
+pub const HOOK_GRAPPLE_LANDING_DISTANCE: f32 = 25.0;
+/// Tension above which the chain snaps
+pub const HOOK_CHAIN_STRENGTH: f32 = 1000.0;
+/// Tension per pixel the links are stretched past their length
+pub const HOOK_CHAIN_STIFFNESS: f32 = 100.0;
+/// Tension added by a caught item
+pub const HOOK_ITEM_LOAD: f32 = 200.0;
+/// Tension added by the player hanging on an anchored hook
+pub const HOOK_GRAPPLE_LOAD: f32 = 600.0;
+/// How far outside an obstacle's corner the chain bends, so it does not touch the obstacle it is wrapped around
+pub const HOOK_WRAP_OFFSET: f32 = 0.5;
+pub const HOOK_MAX_WRAPS_PER_STEP: usize = 4;
+/// Share of its velocity a link keeps from one step to the next
+pub const HOOK_VERLET_DAMPING: f32 = 0.98;
+/// Pixels/s², pulls a slack rope down the screen
+pub const HOOK_VERLET_GRAVITY: Vec2 = Vec2::new(0.0, 300.0);
+/// Passes over the distance constraints per step. More passes make the rope stretch less
+pub const HOOK_VERLET_ITERATIONS: usize = 10;
+
+pub const DASH_SPEED: Magnitude = Magnitude::new(900.0);
+/// Seconds
+pub const DASH_DURATION: f32 = 0.15;
+pub const DASH_COOLDOWN: f32 = 0.6;
+pub const DASH_INVULNERABLE_DURATION: f32 = 0.12;
+
+/// Hooks the off hand can have out at the same time, next to the main hook
+pub const OFF_HAND_HOOKS: usize = 2;
+
+/// Seconds shoot has to be held for a fully charged throw
+pub const CHARGE_DURATION: f32 = 1.0;
+/// A fully charged hook flies this many times as far, and as fast, as a tapped one
+pub const CHARGE_MAX_FACTOR: f32 = 2.0;
 
 pub trait State {
     type Output: State;
     fn position(&self) -> Position;
     fn direction(&self) -> Direction;
-    fn update(self) -> Self::Output;
+    fn update(self, tick: &Tick) -> Self::Output;
 }
 
 
@@ -1,13 +1,15 @@
+use crate::state::Tick;
+
 pub trait Action: Sized {
     type OkOutput;
     type ErrOutput;
-    fn execute<T: Execute<Self>>(self, state: T) -> Result<T::OkState, T::ErrState>;
+    fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState>;
 }
 
 pub trait Execute<T: Action>: Sized {
     fn prepare(&self) -> T;
-    fn execute(self) -> Result<Self::OkState, Self::ErrState> {
-        self.prepare().execute(self)
+    fn execute(self, tick: &Tick) -> Result<Self::OkState, Self::ErrState> {
+        self.prepare().execute(self, tick)
     }
     type OkState;
     fn move_to_ok_state(self, output: T::OkOutput) -> Self::OkState;
@@ -1,16 +1,73 @@
 use std::slice;
 
-use crate::{state::state_machine::hook::action::execute_actions, util::Stack};
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    collision::{self, CollisionBox, Part, Triangles, layers::Layer},
+    draw::graphics::hook_graphics::HOOK_GRAPHICS,
+    persistence::{self, FileType},
+    state::state_machine::hook::action::execute_actions,
+    util::Stack,
+};
 
 use super::*;
 
-pub fn build(speed: Magnitude, direction: Direction, origin: Position, amount_of_links: usize) -> Extending {
-    Extending::extend(speed, direction, origin, amount_of_links)
+pub fn build(spec: HookSpec, direction: Direction, origin: Position) -> Extending {
+    Extending::extend(spec, direction, origin)
 }
 
+/// Everything that makes one hook different from another. Speeds are in pixels/s, lengths in pixels
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq)]
+pub struct HookSpec {
+    /// Links paid out before the hook turns back
+    pub amount_links: usize,
+    pub link_length: f32,
+    pub extend_speed: f32,
+    pub contract_speed: f32,
+    pub reel_speed: f32,
+    /// Tension above which the chain snaps
+    pub strength: f32,
+    #[serde(default)]
+    pub physics: ChainPhysics,
+}
+impl HookSpec {
+    /// Reads JSON or TOML, depending on the extension of `path`
+    pub fn load(path: &str) -> anyhow::Result<Self> {
+        persistence::load::<HookSpec>(path, FileType::from_path(path))
+            .map_err(|e| anyhow!("Could not read hook from {path}: {e}"))
+    }
+    /// Falls back to `default`, and reports why, if the file cannot be used
+    pub fn load_or(path: &str, default: HookSpec) -> Self {
+        HookSpec::load(path).unwrap_or_else(|e| {
+            eprintln!("{e}. Using default hook");
+            default
+        })
+    }
+    /// A throw `factor` times as far, and as fast
+    pub fn scaled(self, factor: f32) -> Self {
+        HookSpec {
+            amount_links: (self.amount_links as f32 * factor).round() as usize,
+            extend_speed: self.extend_speed * factor,
+            ..self
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum HookState {
     Extending(Extending),
     Contracting(Contracting),
+    Anchored(Anchored),
+}
+impl HookState {
+    pub fn chain(&self) -> &Chain {
+        match self {
+            HookState::Extending(extending) => extending.chain(),
+            HookState::Contracting(contracting) => contracting.chain(),
+            HookState::Anchored(anchored) => anchored.chain(),
+        }
+    }
 }
 impl State for HookState {
     type Output = Self;
@@ -19,6 +76,7 @@ impl State for HookState {
         match self {
             HookState::Extending(extending) => extending.position(),
             HookState::Contracting(contracting) => contracting.position(),
+            HookState::Anchored(anchored) => anchored.position(),
         }
     }
 
@@ -26,13 +84,15 @@ impl State for HookState {
         match self {
             HookState::Extending(extending) => extending.direction(),
             HookState::Contracting(contracting) => contracting.direction(),
+            HookState::Anchored(anchored) => anchored.direction(),
         }
     }
 
-    fn update(self) -> Self::Output {
+    fn update(self, tick: &Tick) -> Self::Output {
         match self {
-            HookState::Extending(extending) => extending.update(),
-            HookState::Contracting(contracting) => contracting.update(),
+            HookState::Extending(extending) => extending.update(tick),
+            HookState::Contracting(contracting) => contracting.update(tick),
+            HookState::Anchored(anchored) => anchored.update(tick),
         }
     }
 }
@@ -40,31 +100,101 @@ impl State for HookState {
 const EXTENDING_ACTIONS: [action::Action; 2] = [action::Action::Extend, action::Action::StartContract];
 #[derive(Debug)]
 pub struct Extending {
-    max_amount_links: usize,
+    spec: HookSpec,
     chain: Chain,
-    extend_speed: Magnitude,
+    /// What the head touched in the contacts at the end of the last step. Forgotten once the head moves on
+    touching: Touching,
+}
+/// Parts of other state machines an extending hook head reacts to
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Touching {
+    pub obstacle: bool,
+    pub item: bool,
 }
 impl Extending {
+    pub fn spec(&self) -> HookSpec {
+        self.spec
+    }
     pub fn max_links(&self) -> usize {
-        self.max_amount_links
+        self.spec.amount_links
     }
     pub fn extend_speed(&self) -> Magnitude {
-        self.extend_speed
+        Magnitude::from(self.spec.extend_speed)
     }
 
     pub fn chain(&self) -> &Chain {
         &self.chain
     }
+    pub fn touching(&self) -> Touching {
+        self.touching
+    }
+    /// Remembers what the head touched, for the actions of the next step
+    pub fn touch(self, part: Part) -> Self {
+        let Touching { obstacle, item } = self.touching;
+        let touching = Touching {
+            obstacle: obstacle || part == Part::Wall,
+            item: item || part == Part::Item,
+        };
+        Extending { touching, ..self }
+    }
+    /// Turns back with whatever the head has caught, regardless of how far the chain has paid out
+    pub fn catch(self) -> Contracting {
+        Contracting {
+            chain: self.chain.carry(HOOK_ITEM_LOAD),
+            spec: self.spec,
+        }
+    }
+    /// Turns back before the chain has paid out in full
+    pub fn retract(self) -> Contracting {
+        Contracting {
+            chain: self.chain,
+            spec: self.spec,
+        }
+    }
+    /// Turns back like [Extending::retract], only faster
+    pub fn snap_back(self) -> Contracting {
+        let spec = HookSpec {
+            contract_speed: self.spec.contract_speed * HOOK_SNAP_BACK_FACTOR,
+            ..self.spec
+        };
+        Contracting { chain: self.chain, spec }
+    }
+    /// Fixes the head where it currently is. From now on the chain carries the player
+    pub fn anchor(self) -> Anchored {
+        Anchored {
+            chain: self.chain.carry(HOOK_GRAPPLE_LOAD),
+            spec: self.spec,
+        }
+    }
 
-    fn extend(speed: Magnitude, direction: Direction, origin: Position, max_amount_links: usize) -> Self {
+    /// The chain pays out from the new tail position, while the links already out keep following the head
+    pub fn update_tail_position(self, tail_position: Position) -> Extending {
+        let Self { spec, chain, touching } = self;
+        let chain = chain
+            .update_tail_position(tail_position)
+            .drop_overtaken_links()
+            .maybe_remove_link()
+            .pay_out();
+        Extending { spec, chain, touching }
+    }
+
+    fn extend(spec: HookSpec, direction: Direction, origin: Position) -> Self {
         let hook = Hook::new(origin, direction);
         Extending {
-            max_amount_links,
-            chain: Chain::new(hook, origin, HOOK_LINK_LENGTH),
-            extend_speed: speed,
+            spec,
+            chain: Chain::new(hook, origin, &spec),
+            touching: Touching::default(),
         }
     }
 }
+impl action::Snap for Extending {
+    fn tension(&self) -> f32 {
+        self.chain.tension()
+    }
+    fn strength(&self) -> f32 {
+        self.chain.strength()
+    }
+}
 impl State for Extending {
     type Output = HookState;
     fn position(&self) -> Position {
@@ -75,8 +205,8 @@ impl State for Extending {
         self.chain.head_direction()
     }
 
-    fn update(self) -> HookState {
-        execute_actions(EXTENDING_ACTIONS.into(), self.into())
+    fn update(self, tick: &Tick) -> HookState {
+        execute_actions(EXTENDING_ACTIONS.into(), self.into(), tick)
     }
 }
 impl action::Extend for Extending {
@@ -84,21 +214,26 @@ impl action::Extend for Extending {
         self.chain
     }
 
-    fn extend_speed(&self) -> Magnitude {
-        self.extend_speed
+    fn spec(&self) -> HookSpec {
+        self.spec
     }
 
-    fn max_amount_links(&self) -> usize {
-        self.max_amount_links
+    fn into_state(chain: Chain, spec: HookSpec) -> Self {
+        Extending {
+            spec,
+            chain,
+            touching: Touching::default(),
+        }
     }
-
-    fn into_state(chain: Chain, max_amount_links: usize, extend_speed: Magnitude) -> Self {
-        Extending { max_amount_links, chain, extend_speed }
+}
+impl action::FollowTail for Extending {
+    fn update_tail_position(self, tail_position: Position) -> Self {
+        self.update_tail_position(tail_position)
     }
 }
 impl action::StartContract for Extending {
     fn max_amount_links(&self) -> usize {
-        self.max_amount_links
+        self.spec.amount_links
     }
 
     fn amount_links(&self) -> usize {
@@ -106,7 +241,7 @@ impl action::StartContract for Extending {
     }
 
     fn into_state(self) -> Contracting {
-        Contracting { chain: self.chain, contract_speed: HOOK_CONTRACTING_SPEED }
+        Contracting { chain: self.chain, spec: self.spec }
     }
 }
 
@@ -114,12 +249,15 @@ const CONTRACTING_ACTIONS: [action::Action; 0] = [];
 #[derive(Debug)]
 pub struct Contracting {
     chain: Chain,
-    contract_speed: Magnitude,
+    spec: HookSpec,
 }
 
 impl Contracting {
+    pub fn spec(&self) -> HookSpec {
+        self.spec
+    }
     pub fn contract_speed(&self) -> Magnitude {
-        self.contract_speed
+        Magnitude::from(self.spec.contract_speed)
     }
     pub fn chain(&self) -> &Chain {
         &self.chain
@@ -127,29 +265,33 @@ impl Contracting {
     pub fn into_chain(self) -> Chain {
         self.chain
     }
-    fn contract(mut chain: Chain, speed: Magnitude) -> Self {
-        let position = chain.tail().position();
-        Contracting { chain, contract_speed: speed }
-    }
-
-    pub fn contract_self(self, tail_position: Position) -> Contracting {
-        let Self { chain, contract_speed: speed } = self;
+    pub fn contract_self(self, tail_position: Position, dt: f32) -> Contracting {
+        let speed = self.contract_speed();
+        let Self { chain, spec } = self;
         let distance = distance(chain.tail(), &tail_position);
         let chain = chain
             .update_tail_position(tail_position)
-            .move_links_toward_tail(Magnitude::from(distance) + speed)
+            .move_links_toward_tail(Magnitude::from(distance) + speed * dt)
             .maybe_remove_link();
-        Contracting { chain, contract_speed: speed }
+        Contracting { chain, spec }
     }
 
     pub fn update_tail_position(self, tail_position: Position) -> Contracting {
-        let Self { chain, contract_speed: speed } = self;
+        let Self { chain, spec } = self;
         let distance = distance(chain.tail(), &tail_position);
         let chain = chain
             .update_tail_position(tail_position)
             .move_links_toward_tail(Magnitude::from(distance))
             .maybe_remove_link();
-        Contracting { chain, contract_speed: speed }
+        Contracting { chain, spec }
+    }
+}
+impl action::Snap for Contracting {
+    fn tension(&self) -> f32 {
+        self.chain.tension()
+    }
+    fn strength(&self) -> f32 {
+        self.chain.strength()
     }
 }
 impl State for Contracting {
@@ -162,22 +304,105 @@ impl State for Contracting {
         self.chain.head_direction()
     }
 
-    fn update(self) -> Self::Output {
-        execute_actions(CONTRACTING_ACTIONS.into(), self.into())
+    fn update(self, tick: &Tick) -> Self::Output {
+        execute_actions(CONTRACTING_ACTIONS.into(), self.into(), tick)
+    }
+}
+impl action::FollowTail for Contracting {
+    fn update_tail_position(self, tail_position: Position) -> Self {
+        self.update_tail_position(tail_position)
     }
 }
 impl action::Contract for Contracting {
     fn chain(self) -> Chain {
         self.chain
     }
-    fn contract_speed(&self) -> Magnitude {
-        self.contract_speed
+    fn spec(&self) -> HookSpec {
+        self.spec
     }
-    fn into_state(chain: Chain, contract_speed: Magnitude) -> Self {
-        Contracting { chain, contract_speed }
+    fn into_state(chain: Chain, spec: HookSpec) -> Self {
+        Contracting { chain, spec }
     }
 }
 
+const ANCHORED_ACTIONS: [action::Action; 0] = [];
+/// The head is stuck in something solid. Instead of the hook being pulled in, the tail is reeled along the chain
+#[derive(Debug)]
+pub struct Anchored {
+    chain: Chain,
+    spec: HookSpec,
+}
+impl Anchored {
+    pub fn chain(&self) -> &Chain {
+        &self.chain
+    }
+    pub fn anchor(&self) -> Position {
+        self.chain.head().position()
+    }
+    pub fn reel_speed(&self) -> Magnitude {
+        Magnitude::from(self.spec.reel_speed)
+    }
+    /// Pulls the head free and turns back. Nothing hangs on the chain any more
+    pub fn let_go(self) -> Contracting {
+        Contracting {
+            chain: self.chain.carry(0.0),
+            spec: self.spec,
+        }
+    }
+}
+impl action::Snap for Anchored {
+    fn tension(&self) -> f32 {
+        self.chain.tension()
+    }
+    fn strength(&self) -> f32 {
+        self.chain.strength()
+    }
+}
+impl State for Anchored {
+    type Output = HookState;
+    fn position(&self) -> Position {
+        self.chain.head().position()
+    }
+
+    fn direction(&self) -> Direction {
+        self.chain.head_direction()
+    }
+
+    fn update(self, tick: &Tick) -> Self::Output {
+        execute_actions(ANCHORED_ACTIONS.into(), self.into(), tick)
+    }
+}
+impl action::Reel for Anchored {
+    fn chain(self) -> Chain {
+        self.chain
+    }
+    fn spec(&self) -> HookSpec {
+        self.spec
+    }
+    fn into_state(chain: Chain, spec: HookSpec) -> Self {
+        Anchored { chain, spec }
+    }
+}
+
+/// The head of a hook whose chain snapped. It stays where it was left
+#[derive(Debug)]
+pub struct Dropped {
+    position: Position,
+    direction: Direction,
+}
+impl Dropped {
+    pub fn position(&self) -> Position {
+        self.position
+    }
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+}
+
+fn head_triangles(chain: &Chain) -> Triangles {
+    HOOK_GRAPHICS.model.rotate(chain.head_direction()).translate(chain.head().position()).into()
+}
+
 #[derive(Debug)]
 pub struct Hook {
     direction: Direction,
@@ -202,41 +427,117 @@ impl Hook {
     }
 }
 
+/// How the links between the head and the tail move
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainPhysics {
+    /// Every link is dragged to one link length from its neighbour. A rigid rope without inertia
+    #[default]
+    Kinematic,
+    /// Links keep their velocity from step to step and are only pulled back where the rope is stretched, so it sags,
+    /// swings and whips. The tail and the end driving the chain are pinned: the head while extending, the last link
+    /// while contracting
+    Verlet,
+}
+
+/// Which end of the rope stays put while the links are pulled back together
+enum Pinned {
+    Head,
+    Last,
+}
+
+/// A corner of an obstacle the chain is bent around, somewhere between the last link and the tail
+#[derive(Debug, Clone, Copy)]
+struct Wrap {
+    corner: Position,
+    /// Sign of the turn the chain takes at the corner. Once it turns the other way, the chain comes off the corner
+    turn: f32,
+}
+
 #[derive(Debug)]
 pub struct Chain {
     chain: Stack<Link, Hook, Tail>,
     link_length: f32,
+    physics: ChainPhysics,
+    /// Ordered from the last link to the tail
+    wraps: Vec<Wrap>,
+    /// Links the chain can pay out, past that it is pulled taut and stretches
+    max_links: usize,
+    /// Tension above which the chain snaps
+    strength: f32,
+    /// Pull of whatever hangs on the head, e.g. a caught item
+    load: f32,
+    /// How far the links were left stretched past their length by the last pass over them. Only kept for a Verlet
+    /// chain, a kinematic one is measured along its path
+    stretch: f32,
 }
 
 impl Chain {
-    fn new(hook: Hook, tail_position: Position, link_length: f32) -> Self {
-        let stack = Stack::new(hook, Tail::new(tail_position), |left, right| {
-            Link::clamp_to_length_mut(left, right, HOOK_LINK_LENGTH)
-        });
+    fn new(hook: Hook, tail_position: Position, spec: &HookSpec) -> Self {
+        let stack = Stack::new(hook, Tail::new(tail_position), spec.link_length, Link::clamp_to_length_mut);
         Self {
             chain: stack,
-            link_length,
+            link_length: spec.link_length,
+            physics: spec.physics,
+            wraps: vec![],
+            max_links: spec.amount_links,
+            strength: spec.strength,
+            load: 0.0,
+            stretch: 0.0,
         }
     }
 
+    /// How hard the chain is pulled: how far the links are stretched past their length, plus the load on the head
+    pub fn tension(&self) -> f32 {
+        self.stretch() * HOOK_CHAIN_STIFFNESS + self.load
+    }
+    /// The links of a kinematic chain always keep their length, so it is stretched where the path from the head to
+    /// the tail is longer than the links paid out can span, e.g. when the tail is pulled away from a chain that has
+    /// paid out all its links
+    fn stretch(&self) -> f32 {
+        match self.physics {
+            ChainPhysics::Kinematic => (self.length_of_links() - (self.count() + 1) as f32 * self.link_length).max(0.0),
+            ChainPhysics::Verlet => self.stretch,
+        }
+    }
+    pub fn strength(&self) -> f32 {
+        self.strength
+    }
+    fn carry(self, load: f32) -> Self {
+        Chain { load, ..self }
+    }
+
     fn length_straight_line(&self) -> f32 {
         self.first().distance(self.last())
     }
 
+    /// Length from the head to the tail, around the corners the chain is wrapped on
     fn length_of_links(&self) -> f32 {
+        path_length(&self.path())
+    }
+    /// Every point the chain passes through, from the head over the links and the corners it is wrapped on to the tail
+    pub fn path(&self) -> Vec<Position> {
         self.chain
-            .iter()
-            .skip(1)
-            .fold((0.0, self.first()), |acc, link| {
-                (acc.0 + link.clone().distance(acc.1), link)
-            })
-            .0
+            .iter_full()
+            .map(Link::position)
+            .take(self.chain.len() + 1)
+            .chain(self.tail_path().into_iter().skip(1))
+            .collect()
+    }
+    /// The part of the path from the last link to the tail. This is where the chain pays out and wraps
+    fn tail_path(&self) -> Vec<Position> {
+        std::iter::once(self.last().position())
+            .chain(self.wraps.iter().map(|wrap| wrap.corner))
+            .chain(std::iter::once(self.tail().position()))
+            .collect()
     }
     pub fn head(&self) -> &Hook {
         self.chain.head()
     }
+    /// Where the head points, away from the first link. A head that still sits on its first link points the way it
+    /// was thrown
     pub fn head_direction(&self) -> Direction {
-        self.chain.first().direction(self.chain.head())
+        self.chain.first().direction(self.chain.head()).or(self.head().direction())
     }
     pub fn tail(&self) -> &Tail {
         self.chain.tail()
@@ -282,20 +583,92 @@ impl Chain {
         self.chain.into_iter()
     }
 
-    fn maybe_add_link(mut self) -> Self {
-        if distance(self.tail(), self.last()) > self.link_length {
-            self.chain.push_tail();
+    /// Length of the chain pulled taut from the head to the tail, around the corners it is wrapped on
+    fn taut_length(&self) -> f32 {
+        let corners = self.wraps.iter().map(|wrap| wrap.corner);
+        let path = std::iter::once(self.head().position())
+            .chain(corners)
+            .chain(std::iter::once(self.tail().position()))
+            .collect::<Vec<_>>();
+        path_length(&path)
+    }
+
+    /// Adds links until the chain reaches the tail again. Each new link goes one link length from the last one toward
+    /// the tail, along the bend if the chain is wrapped. Placing it at the tail would leave it too far from the last
+    /// link when the tail moves away.
+    /// A kinematic chain pays out while the last link is more than a link length from the tail. A Verlet chain pays out
+    /// while the links could not reach from the head to the tail pulled taut, so sagging alone does not add links.
+    /// Neither pays out more than the hook's links
+    fn pay_out(mut self) -> Self {
+        while self.count() < self.max_links {
+            let tail_path = self.tail_path();
+            let short = match self.physics {
+                ChainPhysics::Kinematic => path_length(&tail_path) > self.link_length,
+                ChainPhysics::Verlet => self.taut_length() > (self.count() + 1) as f32 * self.link_length,
+            };
+            if !short {
+                break;
+            }
+            let (position, passed) = walk(&tail_path, self.link_length);
+            self.wraps.drain(..passed);
+            self.chain.push(Link::new(position));
         }
         self
     }
 
     fn maybe_remove_link(mut self) -> Self {
-        if distance(self.tail(), self.last()) < HOOK_LINK_DIST_TREAT_AS_ZERO {
+        if self.wraps.is_empty() && distance(self.tail(), self.last()) < HOOK_LINK_DIST_TREAT_AS_ZERO {
+            self.chain.pop();
+        }
+        self
+    }
+
+    /// Drops the last link while the tail can reach the link before it, so the links do not bunch up behind a tail
+    /// that moves toward the head
+    fn drop_overtaken_links(mut self) -> Self {
+        while self.wraps.is_empty() && !self.is_empty() {
+            let before_last = self.chain.iter_full().rev().nth(2).unwrap().position();
+            if self.tail().position().distance(&before_last) > self.link_length {
+                break;
+            }
             self.chain.pop();
         }
         self
     }
 
+    /// Bends the chain between the last link and the tail around the corners of obstacles it would otherwise pass
+    /// through, and straightens it where it has swung back past a corner
+    fn wrap_around(mut self, obstacles: &[CollisionBox]) -> Self {
+        self.unwrap();
+        let previous_tail = self.tail().0.previous;
+        for _ in 0..HOOK_MAX_WRAPS_PER_STEP {
+            let anchor = self.wraps.last().map_or(self.last().position(), |wrap| wrap.corner);
+            match find_wrap(anchor, previous_tail, self.tail().position(), obstacles) {
+                Some(wrap) => self.wraps.push(wrap),
+                None => break,
+            }
+        }
+        self.unwrap();
+        let tail = self.tail().position();
+        self.chain.tail_mut().0.previous = tail;
+        self
+    }
+
+    fn unwrap(&mut self) {
+        loop {
+            let tail_path = self.tail_path();
+            let reversed = self.wraps.iter().enumerate().position(|(i, wrap)| {
+                turn(tail_path[i], tail_path[i + 1], tail_path[i + 2]) * wrap.turn < 0.0
+            });
+            match reversed {
+                Some(i) => {
+                    self.wraps.remove(i);
+                }
+                None => break,
+            }
+        }
+    }
+
     fn update_head_position(mut self, head_position: Position) -> Chain {
         self.chain.head_mut().set_position(head_position);
         self
@@ -306,16 +679,110 @@ impl Chain {
         self
     }
 
-    fn move_links_toward_head(mut self) -> Self {
-        self.chain = self.chain.fold_into_self();
+    /// Lets the links carry on with the velocity they had in the last step. Does nothing to a kinematic chain
+    fn integrate(mut self, dt: f32) -> Self {
+        if self.physics == ChainPhysics::Verlet {
+            self.chain.iter_mut().for_each(|link| link.integrate(dt));
+        }
         self
     }
 
+    fn move_links_toward_head(mut self) -> Self {
+        match self.physics {
+            ChainPhysics::Kinematic => {
+                self.chain = self.chain.fold_into_self();
+                self
+            }
+            ChainPhysics::Verlet => self.pay_out().relax(Pinned::Head),
+        }
+    }
+
+    /// The last link is pulled along the path to the tail, coming off the corners it passes
     fn move_links_toward_tail(mut self, length: Magnitude) -> Chain {
-        let Chain { mut chain, link_length } = self;
-        let last = chain.pop().move_towards(chain.tail(), length.value());
-        let chain = chain.rfold_into_self(&[last]);
-        Chain { chain, link_length }
+        let (position, passed) = walk(&self.tail_path(), length.value());
+        let Chain { mut chain, mut wraps, .. } = self;
+        wraps.drain(..passed);
+        let mut last = chain.pop();
+        last.set_position(position);
+        match self.physics {
+            ChainPhysics::Kinematic => {
+                let chain = chain.rfold_into_self(&[last]);
+                Chain { chain, wraps, ..self }
+            }
+            ChainPhysics::Verlet => {
+                chain.push(Link::new(position));
+                Chain { chain, wraps, ..self }.relax(Pinned::Last)
+            }
+        }
+    }
+
+    /// Pulls neighbours that are more than a link length apart back together, from the head to the tail. The tail,
+    /// or the first corner the chain is wrapped on, holds the rope like a pinned end
+    fn relax(mut self, pinned: Pinned) -> Self {
+        let mut positions = self.chain.iter_full().map(|link| link.position().value()).collect::<Vec<_>>();
+        positions.pop();
+        positions.push(self.tail_path()[1].value());
+        let end = positions.len() - 1;
+        let last = end - 1;
+        let is_pinned = |i: usize| match pinned {
+            Pinned::Head => i == 0 || i == end,
+            Pinned::Last => i == last || i == end,
+        };
+        for _ in 0..HOOK_VERLET_ITERATIONS {
+            for i in 0..end {
+                let (a, b) = (positions[i], positions[i + 1]);
+                let stretch = a.distance(b) - self.link_length;
+                if stretch <= 0.0 {
+                    continue;
+                }
+                let (share_a, share_b) = match (is_pinned(i), is_pinned(i + 1)) {
+                    (true, true) => continue,
+                    (true, false) => (0.0, 1.0),
+                    (false, true) => (1.0, 0.0),
+                    (false, false) => (0.5, 0.5),
+                };
+                let correction = (b - a).normalize_or_zero() * stretch;
+                positions[i] = a + correction * share_a;
+                positions[i + 1] = b - correction * share_b;
+            }
+        }
+        self.stretch = (0..end)
+            .filter(|&i| !(is_pinned(i) && is_pinned(i + 1)))
+            .map(|i| (positions[i].distance(positions[i + 1]) - self.link_length).max(0.0))
+            .sum();
+        self.chain.head_mut().set_position(Position::from_vec(positions[0]));
+        for (link, position) in self.chain.iter_mut().zip(positions.into_iter().skip(1)) {
+            link.set_position(Position::from_vec(position));
+        }
+        self
+    }
+
+    /// Moves the tail along the chain toward the head, removing the corners and links it passes.
+    /// The tail stops `keep_distance` short of the head
+    fn reel_in(mut self, length: Magnitude, keep_distance: f32) -> Chain {
+        let mut remaining = length.value();
+        while remaining > 0.0 {
+            let target = self.wraps.last().map_or(self.last().position(), |wrap| wrap.corner);
+            let at_head = self.is_straight() && self.is_empty();
+            let distance = distance(self.tail(), &target);
+            if at_head || distance > remaining {
+                let max_distance = if at_head { (distance - keep_distance).max(0.0) } else { distance };
+                let position = self.tail().position().move_towards(target, remaining.min(max_distance));
+                self.chain.tail_mut().set_position(position);
+                break;
+            }
+            self.chain.tail_mut().set_position(target);
+            if self.wraps.pop().is_none() {
+                self.chain.pop();
+            }
+            remaining -= distance;
+        }
+        self
+    }
+
+    /// Not wrapped around any corner
+    pub fn is_straight(&self) -> bool {
+        self.wraps.is_empty()
     }
 
     fn count(&self) -> usize {
@@ -330,10 +797,13 @@ pub fn link_relationship(left: Link, right: Link, link_length: f32) {
 #[derive(Debug, Clone)]
 pub struct Link {
     position: Position,
+    /// Where the link was a step ago. Links use it for [ChainPhysics::Verlet], the tail to find the corners it
+    /// swung the chain around
+    previous: Position,
 }
 impl Link {
     fn new(position: Position) -> Self {
-        Self { position }
+        Self { position, previous: position }
     }
 
     pub fn position(&self) -> Position {
@@ -349,9 +819,17 @@ impl Link {
         self.position = position
     }
 
+    fn integrate(&mut self, dt: f32) {
+        let velocity = self.position.value() - self.previous.value();
+        self.previous = self.position;
+        self.position =
+            Position::from_vec(self.position.value() + velocity * HOOK_VERLET_DAMPING + HOOK_VERLET_GRAVITY * dt * dt);
+    }
+
     fn move_by_vector(self, vec: Vec2) -> Self {
         Link {
             position: Position::from_vec(self.position().value() + vec),
+            ..self
         }
     }
 
@@ -364,6 +842,7 @@ impl Link {
             position: self
                 .position()
                 .move_towards(link.as_ref().position(), distance.min(max_dist)),
+            ..self
         }
     }
 
@@ -372,6 +851,7 @@ impl Link {
         let diff = current - link_length;
         Link {
             position: self.position().move_towards(link.position(), diff.max(0.0)),
+            ..self
         }
     }
 
@@ -383,7 +863,7 @@ impl Link {
 
     fn move_link_projection(self, prev: Link, next: &Link, factor: f32) -> Self {
         let position = project_c_onto_ab(prev.position(), next.position(), self.position(), factor);
-        Link { position }
+        Link { position, ..self }
     }
 
     pub fn distance<T: AsRef<Position>>(&self, position: T) -> f32 {
@@ -398,6 +878,57 @@ fn distance<T: AsRef<Position>, U: AsRef<Position>>(from: &T, to: &U) -> f32 {
     from.as_ref().distance(to.as_ref())
 }
 
+fn path_length(path: &[Position]) -> f32 {
+    path.windows(2).map(|pair| pair[0].distance(&pair[1])).sum()
+}
+
+/// Where going `distance` along `path` ends up, and how many of the points between the ends of `path` it passed
+fn walk(path: &[Position], distance: f32) -> (Position, usize) {
+    let mut remaining = distance;
+    for (passed, pair) in path.windows(2).enumerate() {
+        let segment = pair[0].distance(&pair[1]);
+        if remaining <= segment {
+            return (pair[0].move_towards(pair[1], remaining), passed);
+        }
+        remaining -= segment;
+    }
+    (path[path.len() - 1], path.len().saturating_sub(2))
+}
+
+/// Positive for one way around, negative for the other and zero when `a`, `b` and `c` are on a line
+fn turn(a: Position, b: Position, c: Position) -> f32 {
+    (b.value() - a.value()).perp_dot(c.value() - b.value())
+}
+
+/// The corner the chain from `anchor` to `tail` bends around, if it passes through an obstacle.
+/// Of the corners on the side the tail came from, the chain rests on the one furthest out
+fn find_wrap(anchor: Position, previous_tail: Position, tail: Position, obstacles: &[CollisionBox]) -> Option<Wrap> {
+    let (from, to) = (anchor.value(), tail.value());
+    let point = CollisionBox { lower: from, upper: from };
+    let obstacle = obstacles
+        .iter()
+        .filter_map(|obstacle| Some((obstacle, collision::sweep(&point, to - from, [obstacle])?)))
+        .min_by(|a, b| a.1.total_cmp(&b.1))?
+        .0;
+    let side = |point: Vec2| (to - from).perp_dot(point - from);
+    let came_from = side(previous_tail.value());
+    let center = (obstacle.lower + obstacle.upper) / 2.0;
+    let corner = [
+        obstacle.lower,
+        Vec2::new(obstacle.upper.x, obstacle.lower.y),
+        obstacle.upper,
+        Vec2::new(obstacle.lower.x, obstacle.upper.y),
+    ]
+    .into_iter()
+    .filter(|corner| side(*corner) * came_from > 0.0)
+    .max_by(|a, b| (to - from).angle_between(*a - from).abs().total_cmp(&(to - from).angle_between(*b - from).abs()))?;
+    let corner = Position::from_vec(corner + (corner - center).signum() * HOOK_WRAP_OFFSET);
+    Some(Wrap {
+        corner,
+        turn: turn(anchor, corner, tail).signum(),
+    })
+}
+
 fn project_c_onto_ab(a_p: Position, b_p: Position, c_p: Position, factor: f32) -> Position {
     let a = a_p.value();
     let b = b_p.value();
@@ -445,7 +976,7 @@ pub struct Tail(pub Link);
 pub struct Head(Link);
 impl Tail {
     fn new(position: Position) -> Self {
-        Tail(Link { position })
+        Tail(Link::new(position))
     }
     pub fn position(&self) -> Position {
         self.0.position()
@@ -456,7 +987,7 @@ impl Tail {
 }
 impl Head {
     fn new(position: Position) -> Self {
-        Head(Link { position })
+        Head(Link::new(position))
     }
     pub fn position(&self) -> Position {
         self.0.position()
@@ -519,15 +1050,40 @@ impl AsRef<Position> for Hook {
     }
 }
 //* Std trait implementations */
+impl Display for HookState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HookState::Extending(state) => write!(f, "{}", state),
+            HookState::Contracting(state) => write!(f, "{}", state),
+            HookState::Anchored(state) => write!(f, "{}", state),
+        }
+    }
+}
 impl Display for Extending {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Extending {}, {}, links: {}, length: {}",
+            "Extending {}, {}, links: {}, length: {}, tension: {:.0}/{:.0}",
             self.chain.head().position(),
             self.extend_speed(),
             self.chain().chain.len(),
-            self.chain().length_of_links()
+            self.chain().length_of_links(),
+            self.chain().tension(),
+            self.chain().strength()
+        )
+    }
+}
+impl Display for Anchored {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Anchored {}, {}, links: {}, length: {}, tension: {:.0}/{:.0}",
+            self.anchor(),
+            self.reel_speed(),
+            self.chain().chain.len(),
+            self.chain().length_of_links(),
+            self.chain().tension(),
+            self.chain().strength()
         )
     }
 }
@@ -535,12 +1091,14 @@ impl Display for Contracting {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Contracting {}, {}, {}, links: {}, length: {}",
+            "Contracting {}, {}, {}, links: {}, length: {}, tension: {:.0}/{:.0}",
             self.chain.head().position(),
             self.contract_speed(),
             self.chain.head_direction(),
             self.chain().chain.len(),
-            self.chain().length_of_links()
+            self.chain().length_of_links(),
+            self.chain().tension(),
+            self.chain().strength()
         )
     }
 }
@@ -555,6 +1113,11 @@ impl From<Contracting> for HookState {
         HookState::Contracting(value)
     }
 }
+impl From<Anchored> for HookState {
+    fn from(value: Anchored) -> Self {
+        HookState::Anchored(value)
+    }
+}
 impl<T, U> From<Result<T, U>> for HookState
 where
     T: State + Into<HookState>,
@@ -573,11 +1136,11 @@ pub(super) mod action {
         StartContract,
     }
 
-    pub fn execute_actions(actions: Vec<Action>, executor: HookState) -> HookState {
+    pub fn execute_actions(actions: Vec<Action>, executor: HookState, tick: &Tick) -> HookState {
         let mut state = executor;
         for action in actions {
             state = match action {
-                Action::Extend => try_extend(state),
+                Action::Extend => try_extend(state, tick),
                 Action::StartContract => try_start_contract(state),
             };
         }
@@ -586,43 +1149,89 @@ pub(super) mod action {
 
     pub trait Extend: State {
         fn chain(self) -> Chain;
-        fn extend_speed(&self) -> Magnitude;
-        fn max_amount_links(&self) -> usize;
-        fn into_state(chain: Chain, max_amount_links: usize, extend_speed: Magnitude) -> Self;
+        fn spec(&self) -> HookSpec;
+        fn into_state(chain: Chain, spec: HookSpec) -> Self;
     }
-    pub fn try_extend(state: HookState) -> HookState {
+    pub fn try_extend(state: HookState, tick: &Tick) -> HookState {
         match state {
-            HookState::Extending(state) => extend(state).into(),
+            HookState::Extending(state) => extend(state, tick).into(),
             _ => state,
         }
     }
-    pub fn extend<T: Extend>(state: T) -> T {
-        let max_amount_links = state.max_amount_links();
-        let extend_speed = state.extend_speed();
+    /// The head is swept along its path, so it stops just inside the first obstacle or item it meets instead of
+    /// passing through it. Layers the head does not collide with are passed through, as it would never touch them
+    pub fn extend<T: Extend>(state: T, tick: &Tick) -> T {
+        let spec = state.spec();
         let chain = state.chain();
-        let position = calculate_new_head_position(&chain, extend_speed);
+        let position = calculate_new_head_position(&chain, Magnitude::from(spec.extend_speed), tick.dt);
+        let obstacles = if tick.masks.collides(Layer::Hook, Layer::Wall) { tick.obstacles } else { &[] };
+        let items = if tick.masks.collides(Layer::Hook, Layer::Item) { tick.items } else { &[] };
+        let position = stop_at_first_impact(&chain, position, obstacles, items);
         let chain = chain
             .update_head_position(position)
+            .integrate(tick.dt)
             .move_links_toward_head()
-            .maybe_add_link();
-        Extend::into_state(chain, max_amount_links, extend_speed)
+            .wrap_around(tick.obstacles)
+            .pay_out();
+        Extend::into_state(chain, spec)
     }
 
     pub trait Contract: State {
         fn chain(self) -> Chain;
-        fn contract_speed(&self) -> Magnitude;
-        fn into_state(chain: Chain, contract_speed: Magnitude) -> Self;
+        fn spec(&self) -> HookSpec;
+        fn into_state(chain: Chain, spec: HookSpec) -> Self;
     }
-    pub fn contract<T: Contract>(state: T) -> Option<T> {
-        let speed = state.contract_speed();
+    pub fn contract<T: Contract>(state: T, tick: &Tick) -> Option<T> {
+        let spec = state.spec();
         let chain = state.chain();
         let chain = chain
-            .move_links_toward_tail(speed)
+            .wrap_around(tick.obstacles)
+            .integrate(tick.dt)
+            .move_links_toward_tail(Magnitude::from(spec.contract_speed) * tick.dt)
             .maybe_remove_link();
         if chain.is_empty() {
             None
         } else {
-            Some(Contract::into_state(chain, speed))
+            Some(Contract::into_state(chain, spec))
+        }
+    }
+
+    /// The tail of the chain is attached to something that moves on its own, e.g. the player
+    pub trait FollowTail: State {
+        fn update_tail_position(self, tail_position: Position) -> Self;
+    }
+
+    pub trait Reel: State {
+        fn chain(self) -> Chain;
+        fn spec(&self) -> HookSpec;
+        fn into_state(chain: Chain, spec: HookSpec) -> Self;
+    }
+    /// Returns the position of the tail as `Err` once it has been reeled in next to the head
+    pub fn reel<T: Reel>(state: T, dt: f32) -> Result<T, Position> {
+        let spec = state.spec();
+        let chain = state.chain().reel_in(Magnitude::from(spec.reel_speed) * dt, HOOK_GRAPPLE_LANDING_DISTANCE);
+        let landing_distance = HOOK_GRAPPLE_LANDING_DISTANCE + HOOK_LINK_DIST_TREAT_AS_ZERO;
+        if chain.is_empty() && chain.is_straight() && distance(chain.tail(), chain.head()) <= landing_distance {
+            Err(chain.tail().position())
+        } else {
+            Ok(Reel::into_state(chain, spec))
+        }
+    }
+
+    /// A hook on a chain that can be pulled apart
+    pub trait Snap: State {
+        fn tension(&self) -> f32;
+        fn strength(&self) -> f32;
+    }
+    /// The chain gives way once it is pulled harder than it can hold, leaving the head behind
+    pub fn snap<T: Snap>(state: T) -> Result<Dropped, T> {
+        if state.tension() > state.strength() {
+            Ok(Dropped {
+                position: state.position(),
+                direction: state.direction(),
+            })
+        } else {
+            Err(state)
         }
     }
 
@@ -647,11 +1256,186 @@ pub(super) mod action {
         }
     }
 
-    fn calculate_new_head_position(chain: &Chain, speed: Magnitude) -> Position {
+    /// A head that is already inside something stays where it is. Anything else is only met where the triangles of
+    /// the head touch it, as a head stopped short of that would never make the contact that grapples or catches
+    fn stop_at_first_impact(
+        chain: &Chain,
+        position: Position,
+        obstacles: &[CollisionBox],
+        items: &[Triangles],
+    ) -> Position {
+        let head = chain.head().position();
+        let distance = head.distance(&position);
+        let motion = position.value() - head.value();
+        let obstacles: Vec<Triangles> = obstacles.iter().map(|&obstacle| obstacle.into()).collect();
+        match collision::sweep_triangles(&head_triangles(chain), motion, obstacles.iter().chain(items)) {
+            Some(0.0) => head,
+            Some(time_of_impact) => {
+                head.move_towards(position, (distance * time_of_impact + HOOK_SWEEP_SKIN).min(distance))
+            }
+            None => position,
+        }
+    }
+
+    fn calculate_new_head_position(chain: &Chain, speed: Magnitude, dt: f32) -> Position {
         Physics::calculate_new_position_from_speed(
             chain.head().position(),
             speed,
             chain.head().direction(),
+            dt,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::collision::layers::CollisionMasks;
+    use crate::state::hook::HOOK_SPEC;
+
+    /// Pays the chain out to the right for half a second, then holds the head still for `hold` seconds
+    fn extend_and_hold(spec: HookSpec, hold: f32) -> Chain {
+        let origin = Position::new(200.0, 200.0);
+        let dt = 1.0 / 60.0;
+        let mut chain = Chain::new(Hook::new(origin, RIGHT), origin, &spec);
+        for tick in 0..30 + (hold / dt).round() as usize {
+            let paid_out = spec.extend_speed * dt * tick.min(30) as f32;
+            let head = Position::new(origin.x() + paid_out, origin.y());
+            chain = chain
+                .update_head_position(head)
+                .integrate(dt)
+                .move_links_toward_head()
+                .pay_out();
+        }
+        chain
+    }
+
+    #[test]
+    fn verlet_chain_sags_without_stretching() {
+        let chain = extend_and_hold(HookSpec { physics: ChainPhysics::Verlet, ..HOOK_SPEC }, 1.0);
+        let links = chain.chain().iter_full().map(|link| link.position()).collect::<Vec<_>>();
+
+        assert!(links.iter().any(|link| link.y() > 205.0), "{:?}", links);
+        for (a, b) in links[..links.len() - 1].iter().zip(&links[1..links.len() - 1]) {
+            assert!(a.distance(b) <= HOOK_LINK_LENGTH + 0.5, "{} to {}", a, b);
+        }
+    }
+
+    #[test]
+    fn verlet_chain_held_still_keeps_its_links() {
+        let spec = HookSpec { physics: ChainPhysics::Verlet, ..HOOK_SPEC };
+        let settled = extend_and_hold(spec, 1.0);
+        let held = extend_and_hold(spec, 9.0);
+
+        assert_eq!(held.count(), settled.count());
+        assert!(held.tension() < held.strength(), "{}", held.tension());
+    }
+
+    #[test]
+    fn chain_wraps_around_a_corner_and_comes_off_it_again() {
+        let wall = CollisionBox { lower: Vec2::new(250.0, 230.0), upper: Vec2::new(270.0, 400.0) };
+        let head = Position::new(400.0, 200.0);
+        let tail = Position::new(200.0, 200.0);
+        let mut chain = Chain::new(Hook::new(head, RIGHT), tail, &HOOK_SPEC);
+        let move_tail = |chain: Chain, y: f32| chain.update_tail_position(Position::new(200.0, y)).wrap_around(&[wall]);
+
+        for y in (200..=350).step_by(5) {
+            chain = move_tail(chain, y as f32);
+        }
+        assert_eq!(chain.path().len(), 3, "{:?}", chain.path());
+        assert!(chain.path()[1].distance(&Position::new(250.0, 230.0)) < 1.0, "{:?}", chain.path());
+        assert!(chain.length_of_links() > head.distance(&chain.tail().position()));
+
+        for y in (200..=350).rev().step_by(5) {
+            chain = move_tail(chain, y as f32);
+        }
+        assert!(chain.is_straight(), "{:?}", chain.path());
+    }
+
+    #[test]
+    fn chain_snaps_under_a_load_it_cannot_hold() {
+        let origin = Position::new(200.0, 200.0);
+        let weak = build(HookSpec { strength: HOOK_ITEM_LOAD / 2.0, ..HOOK_SPEC }, RIGHT, origin);
+        let strong = build(HOOK_SPEC, RIGHT, origin);
+
+        assert!(action::snap(weak.catch()).is_ok_and(|dropped| dropped.position() == origin));
+        assert!(action::snap(strong.catch()).is_err());
+    }
+
+    #[test]
+    fn paid_out_chain_snaps_when_a_dash_pulls_on_it() {
+        let origin = Position::new(1000.0, 200.0);
+        let spawned = RefCell::new(vec![]);
+        let tick = Tick {
+            dt: 1.0 / 60.0,
+            input: InputFrame::default(),
+            obstacles: &[],
+            items: &[],
+            hooks: &[],
+            masks: &CollisionMasks::default(),
+            spawned: &spawned,
+        };
+        let mut hook = build(HOOK_SPEC, RIGHT, origin);
+        while hook.chain().count() < HOOK_SPEC.amount_links {
+            hook = action::extend(hook, &tick);
+        }
+        assert!(hook.chain().tension() < hook.chain().strength());
+
+        for step in 1..=(DASH_DURATION / tick.dt) as usize {
+            let tail = Position::new(origin.x() - DASH_SPEED.value() * tick.dt * step as f32, origin.y());
+            hook = action::extend(hook, &tick).update_tail_position(tail);
+        }
+        assert!(action::snap(hook).is_ok());
+    }
+
+    #[test]
+    fn extending_head_stays_in_a_thin_wall_over_several_steps() {
+        let wall = CollisionBox { lower: Vec2::new(298.0, 150.0), upper: Vec2::new(302.0, 250.0) };
+        let spawned = RefCell::new(vec![]);
+        let tick = Tick {
+            dt: 0.5,
+            input: InputFrame::default(),
+            obstacles: &[wall],
+            items: &[],
+            hooks: &[],
+            masks: &CollisionMasks::default(),
+            spawned: &spawned,
+        };
+        let mut hook = build(HOOK_SPEC, RIGHT, Position::new(200.0, 200.0));
+
+        for _ in 0..3 {
+            hook = action::extend(hook, &tick);
+            assert!(head_triangles(hook.chain()).overlaps(&wall.into()), "{}", hook);
+        }
+    }
+
+    #[test]
+    fn kinematic_chain_stays_straight() {
+        let chain = extend_and_hold(HOOK_SPEC, 1.0);
+
+        assert!(chain.chain().iter_full().all(|link| link.position().y() == 200.0));
+    }
+
+    #[test]
+    fn links_are_spaced_by_the_spec() {
+        let spec = HookSpec { link_length: 8.0, ..HOOK_SPEC };
+        let chain = extend_and_hold(spec, 1.0);
+        let links = chain.chain().iter_full().map(|link| link.position()).collect::<Vec<_>>();
+
+        for (a, b) in links.iter().zip(&links[1..links.len() - 1]) {
+            assert!((a.distance(b) - spec.link_length).abs() < 0.01, "{} to {}", a, b);
+        }
+    }
+
+    #[test]
+    fn spec_is_read_from_json() {
+        let path = std::env::temp_dir().join("hook_spec.json");
+        let path = path.to_str().unwrap();
+        let spec = HookSpec { link_length: 12.0, physics: ChainPhysics::Verlet, ..HOOK_SPEC };
+        persistence::save(&spec, path, FileType::Json);
+
+        assert_eq!(HookSpec::load(path).unwrap(), spec);
+    }
+}
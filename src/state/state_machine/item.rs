@@ -1,15 +1,20 @@
+use crate::collision::Owner;
+use crate::draw::graphics::item_graphics::ITEM_GRAPHICS;
 use crate::state::item::ItemStateMachine;
 
 use super::*;
 
+pub const ITEM_TURN_RATE: Angle<Degrees> = Angle(Degrees(30.0));
+
 pub fn build(position: Position, direction: Direction, speed: Magnitude) -> Moving {
-    Moving::action(position, direction, speed)
+    Moving { position, direction, speed }
 }
 
 #[derive(Debug)]
 pub enum ItemState {
     Moving(Moving),
     Hooked(Hooked),
+    Collected(Collected),
 }
 impl State for ItemState {
     type Output = Self;
@@ -17,18 +22,21 @@ impl State for ItemState {
         match self {
             ItemState::Moving(moving) => moving.position(),
             ItemState::Hooked(hooked) => hooked.position(),
+            ItemState::Collected(collected) => collected.position(),
         }
     }
     fn direction(&self) -> Direction {
         match self {
             ItemState::Moving(moving) => moving.direction(),
             ItemState::Hooked(hooked) => hooked.direction(),
+            ItemState::Collected(collected) => collected.direction(),
         }
     }
-    fn update(self) -> Self {
+    fn update(self, tick: &Tick) -> Self {
         match self {
-            ItemState::Moving(moving) => moving.update(),
-            ItemState::Hooked(hooked) => hooked.update(),
+            ItemState::Moving(moving) => moving.update(tick),
+            ItemState::Hooked(hooked) => hooked.update(tick),
+            ItemState::Collected(collected) => collected.update(tick),
         }
     }
 }
@@ -55,9 +63,17 @@ impl Moving {
     pub fn speed(&self) -> Magnitude {
         self.speed
     }
-    fn action(position: Position, direction: Direction, speed: Magnitude) -> Self {
-        let new_direction = direction.rotate(Angle(Degrees(0.5)));
-        let new_position = Physics::calculate_new_position_from_speed(position, speed, new_direction);
+    /// Caught by the head of `hook` at the spot where the two touched
+    pub fn hooked(self, at: Position, hook: Owner) -> Hooked {
+        Hooked {
+            position: at,
+            direction: self.direction,
+            hook,
+        }
+    }
+    fn action(position: Position, direction: Direction, speed: Magnitude, dt: f32) -> Self {
+        let new_direction = direction.rotate(Angle(Degrees(ITEM_TURN_RATE.value().value() * dt)));
+        let new_position = Physics::calculate_new_position_from_speed(position, speed, new_direction, dt);
         Moving {
             position: new_position,
             direction: new_direction,
@@ -76,24 +92,31 @@ impl State for Moving {
         self.direction
     }
 
-    fn update(self) -> Self::Output {
+    fn update(self, tick: &Tick) -> Self::Output {
         let Self {
             position,
             direction,
             speed,
         } = self;
-        ItemState::Moving(Moving::action(position, direction, speed))
+        ItemState::Moving(Moving::action(position, direction, speed, tick.dt))
     }
 }
 
+/// Caught by a hook. Rides on its head until it touches the player
 #[derive(Debug)]
 pub struct Hooked {
     position: Position,
     direction: Direction,
+    /// The head that caught the item
+    hook: Owner,
 }
 impl Hooked {
-    fn hook(position: Position, direction: Direction) -> Self {
-        Hooked { position, direction }
+    /// Reached the player
+    pub fn collected(self) -> Collected {
+        Collected {
+            position: self.position,
+            direction: self.direction,
+        }
     }
 }
 impl State for Hooked {
@@ -106,8 +129,38 @@ impl State for Hooked {
         self.direction
     }
 
-    fn update(self) -> Self::Output {
-        todo!()
+    /// Follows the head of the hook that caught it, and no other. Once that hook is gone, e.g. because its chain
+    /// snapped, the item is dropped where it is
+    fn update(self, tick: &Tick) -> Self::Output {
+        match tick.hooks.iter().find(|head| head.owner.same_child(&self.hook)) {
+            Some(head) => ItemState::Hooked(Hooked {
+                position: head.position,
+                direction: head.direction,
+                ..self
+            }),
+            None => ItemState::Moving(build(self.position, self.direction, Magnitude::zero())),
+        }
+    }
+}
+
+/// Delivered to the player. No longer drawn or collided with
+#[derive(Debug)]
+pub struct Collected {
+    position: Position,
+    direction: Direction,
+}
+impl State for Collected {
+    type Output = ItemState;
+    fn position(&self) -> Position {
+        self.position
+    }
+
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    fn update(self, _: &Tick) -> Self::Output {
+        ItemState::Collected(self)
     }
 }
 
@@ -125,3 +178,8 @@ impl Display for Hooked {
         write!(f, "{} {}", name_of_type(self), self.position)
     }
 }
+impl Display for Collected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", name_of_type(self), self.position)
+    }
+}
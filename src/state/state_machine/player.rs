@@ -1,5 +1,6 @@
 //! Module handling player states
 
+use crate::collision::Part;
 use crate::state::state_machine::action::{Action, Execute};
 
 use super::*;
@@ -8,15 +9,16 @@ use action::*;
 // Workaround for Tracking Issue "More Qualified Paths": https://github.com/rust-lang/rust/issues/86935#issuecomment-1146670057
 type Type<T> = T;
 
-pub fn build(position: Position, direction: Direction, speed: Magnitude) -> Idling {
-    Idling::idle(position, direction, speed)
+pub fn build(position: Position, direction: Direction, physics: Physics, hook: HookSpec) -> Idling {
+    Idling::idle(position, direction, physics, hook)
 }
 
 #[derive(Debug)]
 pub enum PlayerState {
     Idling(Idling),
-    ParentChildIdlingExtending(ParentChild<Idling, Extending>),
-    ParentChildIdlingContracting(ParentChild<Idling, Contracting>),
+    Charging(Charging),
+    Dashing(Dashing),
+    Grapled(Grapled),
 }
 impl State for PlayerState {
     type Output = Self;
@@ -24,8 +26,9 @@ impl State for PlayerState {
     fn position(&self) -> Position {
         match self {
             PlayerState::Idling(idling) => idling.position(),
-            PlayerState::ParentChildIdlingExtending(parent_child) => parent_child.position(),
-            PlayerState::ParentChildIdlingContracting(parent_child) => parent_child.position(),
+            PlayerState::Charging(charging) => charging.position(),
+            PlayerState::Dashing(dashing) => dashing.position(),
+            PlayerState::Grapled(grapled) => grapled.position(),
         }
     }
 
@@ -33,25 +36,66 @@ impl State for PlayerState {
         todo!()
     }
 
-    fn update(self) -> Self::Output {
+    fn update(self, tick: &Tick) -> Self::Output {
         todo!()
     }
 }
-
-#[derive(Debug)]
-pub enum Relation {
-    ParentChild,
-    Siblings,
+impl PlayerState {
+    /// Grapples once the main hook has anchored, and is pulled along its chain from then on
+    pub fn follow_hook(self, thrown: &ThrownHook) -> Self {
+        let (Hand::Main, HookState::Anchored(anchored)) = (thrown.hand(), thrown.state()) else {
+            return self;
+        };
+        match self {
+            PlayerState::Idling(state) => state.grapple().into(),
+            PlayerState::Dashing(state) => state.grapple().into(),
+            PlayerState::Grapled(state) => state.pulled_to(anchored.chain().tail().position()).into(),
+            state => state,
+        }
+    }
+    /// The main hook is back in hand once it has returned or its chain has snapped. A player hanging on it lands
+    pub fn hook_finished(self, end: HookEnd) -> Self {
+        match (self, end) {
+            (PlayerState::Grapled(state), HookEnd::Landed(position)) => state.land(position).into(),
+            (PlayerState::Grapled(state), HookEnd::Snapped(Hand::Main)) => Idling::from(state).into(),
+            (PlayerState::Idling(state), HookEnd::Returned(Hand::Main) | HookEnd::Snapped(Hand::Main)) => {
+                state.hook_returned().into()
+            }
+            (PlayerState::Dashing(state), HookEnd::Returned(Hand::Main) | HookEnd::Snapped(Hand::Main)) => {
+                state.hook_returned().into()
+            }
+            (state, _) => state,
+        }
+    }
 }
 
-const IDLING_ACTIONS: [ActionType; 2] = [ActionType::Run, ActionType::Shoot];
+const IDLING_ACTIONS: [ActionType; 5] =
+    [ActionType::Run, ActionType::Aim, ActionType::Dash, ActionType::Shoot, ActionType::Release];
+
+/// Where the main hook is while the player is not hanging on it
+#[derive(Debug, Default, Clone, Copy)]
+pub enum MainHook {
+    #[default]
+    InHand,
+    /// Released from a charge during this step. It leaves the hand once the step is over
+    Thrown(HookSpec),
+    Out,
+}
 
 #[derive(Debug, Default)]
 pub struct Idling {
     position: Position,
+    /// Where the player moves
     direction: Direction,
-    max_speed: Magnitude,
+    /// Where the player looks and shoots the hook
+    facing: Direction,
+    physics: Physics,
+    /// The hook the player shoots
+    hook: HookSpec,
+    main_hook: MainHook,
     current_speed: Magnitude,
+    /// Seconds until the player can dash again
+    dash_cooldown: f32,
     // actions: Vec<IdlingAction>
 }
 impl State for Idling {
@@ -62,19 +106,62 @@ impl State for Idling {
     fn direction(&self) -> Direction {
         self.direction
     }
-    fn update(self) -> PlayerState {
-        execute_actions(IDLING_ACTIONS.into(), self.into())
+    fn update(self, tick: &Tick) -> PlayerState {
+        execute_actions(IDLING_ACTIONS.into(), self.cool_down(tick.dt).into(), tick)
     }
 }
 impl Idling {
-    fn idle(position: Position, direction: Direction, speed: Magnitude) -> Self {
+    pub fn hook(&self) -> HookSpec {
+        self.hook
+    }
+    /// Where the player looks and throws the hook, which is not necessarily where it moves
+    pub fn facing(&self) -> Direction {
+        self.facing
+    }
+    fn idle(position: Position, direction: Direction, physics: Physics, hook: HookSpec) -> Self {
         Idling {
             position,
             direction,
-            max_speed: speed,
+            facing: direction,
+            physics,
+            hook,
             ..Default::default()
         }
     }
+    fn cool_down(self, dt: f32) -> Self {
+        Idling {
+            dash_cooldown: (self.dash_cooldown - dt).max(0.0),
+            ..self
+        }
+    }
+    /// Hands out the main hook once a charge has been released, so that it can fly on its own
+    pub fn let_go(self) -> (Idling, Option<ThrownHook>) {
+        match self.main_hook {
+            MainHook::Thrown(spec) => {
+                let hook = ThrownHook {
+                    hand: Hand::Main,
+                    state: HookState::Extending(hook::build(spec, self.facing, self.position)),
+                };
+                (Idling { main_hook: MainHook::Out, ..self }, Some(hook))
+            }
+            _ => (self, None),
+        }
+    }
+    fn grapple(self) -> Grapled {
+        Grapled {
+            position: self.position,
+            direction: self.direction,
+            physics: self.physics,
+            hook: self.hook,
+            dash_cooldown: self.dash_cooldown,
+        }
+    }
+    fn hook_returned(self) -> Self {
+        Idling {
+            main_hook: MainHook::InHand,
+            ..self
+        }
+    }
     // fn _update(self) -> PlayerState {
     //     run(self)
     //         .and_then(shoot)
@@ -87,7 +174,9 @@ impl Execute<Run> for Idling {
     fn prepare(&self) -> Run {
         Run {
             position: self.position,
-            max_speed: self.max_speed,
+            direction: self.direction,
+            current_speed: self.current_speed,
+            physics: self.physics,
         }
     }
     type OkState = Idling;
@@ -100,42 +189,48 @@ impl Execute<Run> for Idling {
         Self::OkState {
             position,
             direction,
-            max_speed: self.max_speed,
             current_speed,
+            ..self
         }
     }
     type ErrState = Idling;
     fn move_to_err_state(self, output: <Run as Action>::ErrOutput) -> Self::ErrState {
-        let Self {
+        let Type::<<Run as Action>::ErrOutput> { position, current_speed } = output;
+        Self::ErrState {
             position,
-            direction,
-            max_speed,
-            ..
-        } = self;
-        let Type::<<Run as Action>::ErrOutput> { current_speed } = output;
-        Self::OkState {
-            position,
-            direction,
-            max_speed,
             current_speed,
+            ..self
+        }
+    }
+}
+impl Execute<Aim> for Idling {
+    fn prepare(&self) -> Aim {
+        Aim {
+            position: self.position,
+            facing: self.facing,
+        }
+    }
+    type OkState = Idling;
+    fn move_to_ok_state(self, facing: <Aim as Action>::OkOutput) -> Self::OkState {
+        Idling { facing, ..self }
+    }
+    type ErrState = Idling;
+    fn move_to_err_state(self, _: <Aim as Action>::ErrOutput) -> Self::ErrState {
+        Idling {
+            facing: self.direction,
+            ..self
         }
     }
 }
 impl Execute<Shoot> for Idling {
     fn prepare(&self) -> Shoot {
-        Shoot
+        Shoot {
+            hook_in_hand: matches!(self.main_hook, MainHook::InHand),
+        }
     }
-    type OkState = ParentChild<Idling, Extending>;
+    type OkState = Charging;
     fn move_to_ok_state(self, _: <Shoot as Action>::OkOutput) -> Self::OkState {
-        ParentChild {
-            child: hook::build(
-                HOOK_EXTENDING_SPEED,
-                self.direction(),
-                self.position(),
-                HOOK_AMOUNT_LINKS,
-            ),
-            parent: self,
-        }
+        Charging { idling: self, charge: 0.0 }
     }
     type ErrState = Self;
     fn move_to_err_state(self, _: <Shoot as Action>::ErrOutput) -> Self::ErrState {
@@ -143,131 +238,426 @@ impl Execute<Shoot> for Idling {
     }
 }
 
-const GRAPLED_ACTIONS: [ActionType; 0] = [];
-pub struct Grapled {}
-
-const IDLING_EXTENDING: [ActionType; 2] = [ActionType::Extend, ActionType::StartContract];
-const IDLING_CONTRACTING: [ActionType; 2] = [ActionType::Run, ActionType::Contract];
+const CHARGING_ACTIONS: [ActionType; 3] = [ActionType::Run, ActionType::Aim, ActionType::Release];
+/// Shoot is held down. The longer it is held, the farther the hook flies once shoot is released
 #[derive(Debug)]
-pub struct ParentChild<A, B>
-where
-    A: State,
-{
-    parent: A,
-    child: B,
+pub struct Charging {
+    idling: Idling,
+    /// From 0 when shoot went down to 1 at full charge
+    charge: f32,
 }
-impl<A: State, B> ParentChild<A, B> {
-    pub fn parent(&self) -> &A {
-        &self.parent
+impl Charging {
+    pub fn hook(&self) -> HookSpec {
+        self.idling.hook
     }
-    pub fn child(&self) -> &B {
-        &self.child
+    pub fn facing(&self) -> Direction {
+        self.idling.facing
+    }
+    pub fn charge(&self) -> f32 {
+        self.charge
+    }
+    fn charged_hook(&self) -> HookSpec {
+        self.idling.hook.scaled(1.0 + self.charge * (CHARGE_MAX_FACTOR - 1.0))
     }
 }
-impl State for ParentChild<Idling, Extending> {
+impl State for Charging {
     type Output = PlayerState;
     fn position(&self) -> Position {
-        self.parent.position()
+        self.idling.position()
     }
-
     fn direction(&self) -> Direction {
-        self.parent.direction()
+        self.idling.direction()
+    }
+    fn update(self, tick: &Tick) -> PlayerState {
+        let Self { idling, charge } = self;
+        let state = Charging { idling: idling.cool_down(tick.dt), charge };
+        execute_actions(CHARGING_ACTIONS.into(), state.into(), tick)
+    }
+}
+impl Execute<Run> for Charging {
+    fn prepare(&self) -> Run {
+        Execute::<Run>::prepare(&self.idling)
+    }
+    type OkState = Self;
+    fn move_to_ok_state(self, output: <Run as Action>::OkOutput) -> Self::OkState {
+        let Self { idling, charge } = self;
+        Charging { idling: Execute::<Run>::move_to_ok_state(idling, output), charge }
+    }
+    type ErrState = Self;
+    fn move_to_err_state(self, output: <Run as Action>::ErrOutput) -> Self::ErrState {
+        let Self { idling, charge } = self;
+        Charging { idling: Execute::<Run>::move_to_err_state(idling, output), charge }
+    }
+}
+impl Execute<Aim> for Charging {
+    fn prepare(&self) -> Aim {
+        Execute::<Aim>::prepare(&self.idling)
     }
+    type OkState = Self;
+    fn move_to_ok_state(self, output: <Aim as Action>::OkOutput) -> Self::OkState {
+        let Self { idling, charge } = self;
+        Charging { idling: Execute::<Aim>::move_to_ok_state(idling, output), charge }
+    }
+    type ErrState = Self;
+    fn move_to_err_state(self, output: <Aim as Action>::ErrOutput) -> Self::ErrState {
+        let Self { idling, charge } = self;
+        Charging { idling: Execute::<Aim>::move_to_err_state(idling, output), charge }
+    }
+}
+impl Execute<Release> for Charging {
+    fn prepare(&self) -> Release {
+        Release { charge: self.charge }
+    }
+    type OkState = Idling;
+    fn move_to_ok_state(self, _: <Release as Action>::OkOutput) -> Self::OkState {
+        let main_hook = MainHook::Thrown(self.charged_hook());
+        Idling { main_hook, ..self.idling }
+    }
+    type ErrState = Self;
+    fn move_to_err_state(self, charge: <Release as Action>::ErrOutput) -> Self::ErrState {
+        Charging { charge, ..self }
+    }
+}
 
-    fn update(self) -> PlayerState {
-        execute_actions(IDLING_EXTENDING.into(), self.into())
+impl Execute<Dash> for Idling {
+    fn prepare(&self) -> Dash {
+        Dash {
+            cooldown: self.dash_cooldown,
+        }
+    }
+    type OkState = Dashing;
+    fn move_to_ok_state(self, _: <Dash as Action>::OkOutput) -> Self::OkState {
+        Dashing {
+            position: self.position,
+            direction: self.direction,
+            physics: self.physics,
+            hook: self.hook,
+            main_hook: self.main_hook,
+            remaining: DASH_DURATION,
+            invulnerable: DASH_INVULNERABLE_DURATION,
+        }
+    }
+    type ErrState = Self;
+    fn move_to_err_state(self, _: <Dash as Action>::ErrOutput) -> Self::ErrState {
+        self
     }
 }
 
-impl State for ParentChild<Idling, Contracting> {
+const DASHING_ACTIONS: [ActionType; 1] = [ActionType::Burst];
+
+/// A short burst along the direction the player was moving in when the dash started.
+/// Running and shooting are not possible until the dash has ended
+#[derive(Debug)]
+pub struct Dashing {
+    position: Position,
+    direction: Direction,
+    physics: Physics,
+    hook: HookSpec,
+    main_hook: MainHook,
+    /// Seconds left of the dash
+    remaining: f32,
+    /// Seconds left of invulnerability
+    invulnerable: f32,
+}
+impl Dashing {
+    pub fn hook(&self) -> HookSpec {
+        self.hook
+    }
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable > 0.0
+    }
+    fn end(self) -> Idling {
+        Idling {
+            position: self.position,
+            direction: self.direction,
+            facing: self.direction,
+            physics: self.physics,
+            hook: self.hook,
+            main_hook: self.main_hook,
+            current_speed: Magnitude::zero(),
+            dash_cooldown: DASH_COOLDOWN,
+        }
+    }
+    /// The dash ends where the hook anchored
+    fn grapple(self) -> Grapled {
+        Grapled {
+            position: self.position,
+            direction: self.direction,
+            physics: self.physics,
+            hook: self.hook,
+            dash_cooldown: DASH_COOLDOWN,
+        }
+    }
+    fn hook_returned(self) -> Self {
+        Dashing {
+            main_hook: MainHook::InHand,
+            ..self
+        }
+    }
+}
+impl State for Dashing {
     type Output = PlayerState;
     fn position(&self) -> Position {
-        self.parent.position()
+        self.position
     }
     fn direction(&self) -> Direction {
-        self.parent.direction()
+        self.direction
     }
-    fn update(self) -> PlayerState {
-        execute_actions(IDLING_CONTRACTING.into(), self.into())
+    fn update(self, tick: &Tick) -> PlayerState {
+        execute_actions(DASHING_ACTIONS.into(), self.into(), tick)
     }
 }
-impl Execute<Run> for ParentChild<Idling, Contracting> {
-    fn prepare(&self) -> Run {
-        Run {
-            position: self.parent.position,
-            max_speed: self.parent.max_speed,
+impl Execute<Burst> for Dashing {
+    fn prepare(&self) -> Burst {
+        Burst {
+            position: self.position,
+            direction: self.direction,
+            remaining: self.remaining,
+            invulnerable: self.invulnerable,
         }
     }
     type OkState = Self;
-    fn move_to_ok_state(self, output: <Run as Action>::OkOutput) -> Self::OkState {
-        let Self { parent, child } = self;
-        let Type::<<Run as Action>::OkOutput> {
+    fn move_to_ok_state(self, output: <Burst as Action>::OkOutput) -> Self::OkState {
+        let Type::<<Burst as Action>::OkOutput> {
             position,
-            direction,
-            current_speed,
+            remaining,
+            invulnerable,
         } = output;
-        Self::OkState {
-            parent: Idling {
-                position,
-                direction,
-                max_speed: parent.max_speed,
-                current_speed,
-            },
-            child: child.update_tail_position(position),
+        Dashing {
+            position,
+            remaining,
+            invulnerable,
+            ..self
+        }
+    }
+    type ErrState = Idling;
+    fn move_to_err_state(self, _: <Burst as Action>::ErrOutput) -> Self::ErrState {
+        self.end()
+    }
+}
+
+/// Pulled along the chain toward an anchored hook
+#[derive(Debug)]
+pub struct Grapled {
+    position: Position,
+    direction: Direction,
+    physics: Physics,
+    hook: HookSpec,
+    dash_cooldown: f32,
+}
+impl Grapled {
+    pub fn hook(&self) -> HookSpec {
+        self.hook
+    }
+    fn pulled_to(self, position: Position) -> Self {
+        Grapled {
+            direction: Direction::a_to_b(self.position, position).or(self.direction),
+            position,
+            ..self
+        }
+    }
+    fn land(self, position: Position) -> Idling {
+        Idling {
+            position,
+            direction: self.direction,
+            facing: self.direction,
+            physics: self.physics,
+            hook: self.hook,
+            main_hook: MainHook::InHand,
+            current_speed: Magnitude::zero(),
+            dash_cooldown: self.dash_cooldown,
         }
     }
+}
+impl State for Grapled {
+    type Output = PlayerState;
+    fn position(&self) -> Position {
+        self.position
+    }
+    fn direction(&self) -> Direction {
+        self.direction
+    }
+    /// The anchored hook reels the player in, see [PlayerState::follow_hook]
+    fn update(self, _: &Tick) -> PlayerState {
+        self.into()
+    }
+}
+
+impl Execute<Retract> for Extending {
+    fn prepare(&self) -> Retract {
+        Retract
+    }
+    type OkState = Contracting;
+    fn move_to_ok_state(self, _: <Retract as Action>::OkOutput) -> Self::OkState {
+        self.retract()
+    }
     type ErrState = Self;
-    fn move_to_err_state(self, output: <Run as Action>::ErrOutput) -> Self::ErrState {
-        let Self { parent: Idling { position, direction, max_speed, ..}, child } = self;
-        let Type::<<Run as Action>::ErrOutput> { current_speed } = output;
-        Self::OkState {
-            parent: Idling {
-                position,
-                direction,
-                max_speed,
-                current_speed,
-            },
-            child,
+    fn move_to_err_state(self, _: <Retract as Action>::ErrOutput) -> Self::ErrState {
+        self
+    }
+}
+impl Execute<SnapBack> for Extending {
+    fn prepare(&self) -> SnapBack {
+        SnapBack
+    }
+    type OkState = Contracting;
+    fn move_to_ok_state(self, _: <SnapBack as Action>::OkOutput) -> Self::OkState {
+        self.snap_back()
+    }
+    type ErrState = Self;
+    fn move_to_err_state(self, _: <SnapBack as Action>::ErrOutput) -> Self::ErrState {
+        self
+    }
+}
+impl Execute<Graple> for Extending {
+    fn prepare(&self) -> Graple {
+        Graple {
+            touching: self.touching().obstacle,
         }
     }
+    type OkState = Anchored;
+    fn move_to_ok_state(self, _: <Graple as Action>::OkOutput) -> Self::OkState {
+        self.anchor()
+    }
+    type ErrState = Self;
+    fn move_to_err_state(self, _: <Graple as Action>::ErrOutput) -> Self::ErrState {
+        self
+    }
+}
+impl Execute<Catch> for Extending {
+    fn prepare(&self) -> Catch {
+        Catch {
+            touching: self.touching().item,
+        }
+    }
+    type OkState = Contracting;
+    fn move_to_ok_state(self, _: <Catch as Action>::OkOutput) -> Self::OkState {
+        self.catch()
+    }
+    type ErrState = Self;
+    fn move_to_err_state(self, _: <Catch as Action>::ErrOutput) -> Self::ErrState {
+        self
+    }
 }
 
+/// Which hand a hook was thrown from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    /// Charged with shoot. It can grapple and carry the player
+    Main,
+    Off,
+}
+
+/// A hook out of the player's hand, carried as one of its children
 #[derive(Debug)]
-pub struct Duality<A, B>
-where
-    A: State,
-    B: State,
-{
-    ying: A,
-    yang: B,
+pub struct ThrownHook {
+    hand: Hand,
+    state: HookState,
 }
-impl<A, B> Duality<A, B>
-where
-    A: State,
-    B: State,
-{
-    pub fn ying(&self) -> &A {
-        &self.ying
+impl ThrownHook {
+    pub fn hand(&self) -> Hand {
+        self.hand
+    }
+    pub fn state(&self) -> &HookState {
+        &self.state
+    }
+    /// Keeps the tail of the chain on the player. An anchored hook pulls the player instead
+    pub fn follow_tail(self, tail: Position) -> Self {
+        let ThrownHook { hand, state } = self;
+        let state = match state {
+            HookState::Extending(state) => HookState::Extending(state.update_tail_position(tail)),
+            HookState::Contracting(state) => HookState::Contracting(state.update_tail_position(tail)),
+            HookState::Anchored(state) => HookState::Anchored(state),
+        };
+        ThrownHook { hand, state }
     }
-    pub fn yang(&self) -> &B {
-        &self.yang
+    /// Only an extending head reacts to what it touches. It does so with the actions of the next step
+    pub fn head_touched(self, part: Part) -> Self {
+        match self.state {
+            HookState::Extending(state) => ThrownHook {
+                state: HookState::Extending(state.touch(part)),
+                ..self
+            },
+            _ => self,
+        }
     }
 }
 
+/// How a thrown hook finished
+#[derive(Debug)]
+pub enum HookEnd {
+    /// Back in the hand it was thrown from
+    Returned(Hand),
+    /// The chain snapped and the head was left in the world
+    Snapped(Hand),
+    /// The player was reeled in next to the anchor, and lands at this position
+    Landed(Position),
+}
+
+/// Throws a hook from the player's off hand toward `direction`, if asked to and fewer than [OFF_HAND_HOOKS] are out.
+/// Off-hand hooks run their own [Extending]/[Contracting] life cycle next to the main hook, with their tail on the
+/// player, whatever state the player is in
+pub fn throw_off_hand(
+    spec: HookSpec,
+    origin: Position,
+    direction: Direction,
+    out: usize,
+    tick: &Tick,
+) -> Option<ThrownHook> {
+    (tick.input.shoot_off_hand.pressed && out < OFF_HAND_HOOKS).then(|| ThrownHook {
+        hand: Hand::Off,
+        state: HookState::Extending(hook::build(spec, direction, origin)),
+    })
+}
+
+const MAIN_HAND_EXTENDING: [HookActionType; 7] = [
+    HookActionType::Retract,
+    HookActionType::SnapBack,
+    HookActionType::Catch,
+    HookActionType::Graple,
+    HookActionType::Extend,
+    HookActionType::StartContract,
+    HookActionType::Snap,
+];
+const OFF_HAND_EXTENDING: [HookActionType; 5] = [
+    HookActionType::Catch,
+    HookActionType::Graple,
+    HookActionType::Extend,
+    HookActionType::StartContract,
+    HookActionType::Snap,
+];
+const CONTRACTING: [HookActionType; 2] = [HookActionType::Contract, HookActionType::Snap];
+const ANCHORED: [HookActionType; 2] = [HookActionType::Reel, HookActionType::Snap];
+/// `Err` once the hook is back in the hand, has reeled the player in, or its chain has snapped
+pub fn update_hook(thrown: ThrownHook, tick: &Tick) -> Result<ThrownHook, HookEnd> {
+    let actions = match (thrown.hand, &thrown.state) {
+        (Hand::Main, HookState::Extending(_)) => MAIN_HAND_EXTENDING.into(),
+        (Hand::Off, HookState::Extending(_)) => OFF_HAND_EXTENDING.into(),
+        (_, HookState::Contracting(_)) => CONTRACTING.into(),
+        (_, HookState::Anchored(_)) => ANCHORED.into(),
+    };
+    execute_hook_actions(actions, thrown, tick)
+}
+
 //** Std trait implementations */
 impl From<Idling> for PlayerState {
     fn from(value: Idling) -> Self {
         PlayerState::Idling(value)
     }
 }
-impl From<ParentChild<Idling, Extending>> for PlayerState {
-    fn from(value: ParentChild<Idling, Extending>) -> Self {
-        PlayerState::ParentChildIdlingExtending(value)
+impl From<Charging> for PlayerState {
+    fn from(value: Charging) -> Self {
+        PlayerState::Charging(value)
     }
 }
-impl From<ParentChild<Idling, Contracting>> for PlayerState {
-    fn from(value: ParentChild<Idling, Contracting>) -> Self {
-        PlayerState::ParentChildIdlingContracting(value)
+impl From<Dashing> for PlayerState {
+    fn from(value: Dashing) -> Self {
+        PlayerState::Dashing(value)
+    }
+}
+impl From<Grapled> for PlayerState {
+    fn from(value: Grapled) -> Self {
+        PlayerState::Grapled(value)
     }
 }
 impl<T, U> From<Result<T, U>> for PlayerState
@@ -279,6 +669,17 @@ where
         value.map_or_else(|s| s.into(), |s| s.into())
     }
 }
+impl From<Dashing> for Idling {
+    fn from(value: Dashing) -> Self {
+        value.end()
+    }
+}
+impl From<Grapled> for Idling {
+    fn from(value: Grapled) -> Self {
+        let position = value.position;
+        value.land(position)
+    }
+}
 impl From<Result<Idling, Idling>> for Idling {
     fn from(value: Result<Idling, Idling>) -> Self {
         value.unwrap_or_else(|s| s)
@@ -289,22 +690,24 @@ impl Display for Idling {
         write!(f, "{} {}", name_of_type(self), self.position())
     }
 }
-impl<A, B> Display for Duality<A, B>
-where
-    A: State + Display,
-    B: State + Display,
-{
+impl Display for Charging {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Duality\n\tYing {}\n\tYang {}", self.ying(), self.yang())
+        write!(f, "{} {} charge: {:.2}", name_of_type(self), self.position(), self.charge)
     }
 }
-impl<A, B> Display for ParentChild<A, B>
-where
-    A: State + Display,
-    B: Display,
-{
+impl Display for Grapled {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ParentChild\n\tParent {}\n\tChild {}", self.parent(), self.child())
+        write!(f, "{} {}", name_of_type(self), self.position())
+    }
+}
+impl Display for Dashing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", name_of_type(self), self.position(), self.direction())
+    }
+}
+impl Display for ThrownHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:?} hand {}", name_of_type(self), self.hand, self.state)
     }
 }
 
@@ -318,95 +721,198 @@ mod action {
 
     pub(super) enum ActionType {
         Run,
+        Aim,
         Shoot,
-        Extend,
-        Contract,
-        StartContract,
+        Release,
         Dash,
-        Graple,
+        Burst,
     }
 
-    pub(super) fn execute_actions(actions: Vec<ActionType>, executor: PlayerState) -> PlayerState {
+    pub(super) fn execute_actions(actions: Vec<ActionType>, executor: PlayerState, tick: &Tick) -> PlayerState {
         let mut state = executor;
         for action in actions {
             state = match action {
-                ActionType::Run => try_run(state),
-                ActionType::Shoot => try_shoot(state),
-                ActionType::Extend => try_extend(state),
-                ActionType::Contract => try_contract(state),
-                ActionType::StartContract => try_start_contract(state),
-                ActionType::Dash => todo!(),
-                ActionType::Graple => todo!(),
+                ActionType::Run => try_run(state, tick),
+                ActionType::Aim => try_aim(state, tick),
+                ActionType::Shoot => try_shoot(state, tick),
+                ActionType::Release => try_release(state, tick),
+                ActionType::Dash => try_dash(state, tick),
+                ActionType::Burst => try_burst(state, tick),
             };
         }
         state
     }
-    pub(super) fn try_shoot(state: PlayerState) -> PlayerState {
+    pub(super) fn try_shoot(state: PlayerState, tick: &Tick) -> PlayerState {
         match state {
-            PlayerState::Idling(state) => Execute::<Shoot>::execute(state).into(),
+            PlayerState::Idling(state) => Execute::<Shoot>::execute(state, tick).into(),
             _ => state,
         }
     }
-    pub(super) fn try_run(state: PlayerState) -> PlayerState {
+    pub(super) fn try_release(state: PlayerState, tick: &Tick) -> PlayerState {
         match state {
-            PlayerState::Idling(state) => Execute::<Run>::execute(state).into(),
-            PlayerState::ParentChildIdlingContracting(state) => Execute::<Run>::execute(state).into(),
+            PlayerState::Charging(state) => Execute::<Release>::execute(state, tick).into(),
             _ => state,
         }
     }
-
-    pub(super) fn try_extend(state: PlayerState) -> PlayerState {
+    pub(super) fn try_run(state: PlayerState, tick: &Tick) -> PlayerState {
         match state {
-            PlayerState::ParentChildIdlingExtending(state) => {
-                let ParentChild { parent, child } = state;
-                ParentChild {
-                    parent,
-                    child: hook::action::extend(child),
-                }
-                .into()
-            }
+            PlayerState::Idling(state) => Execute::<Run>::execute(state, tick).into(),
+            PlayerState::Charging(state) => Execute::<Run>::execute(state, tick).into(),
             _ => state,
         }
     }
 
-    pub(super) fn try_contract(state: PlayerState) -> PlayerState {
+    pub(super) fn try_aim(state: PlayerState, tick: &Tick) -> PlayerState {
         match state {
-            PlayerState::ParentChildIdlingContracting(state) => {
-                let ParentChild { parent, child } = state;
-                if let Some(child) = hook::action::contract(child) {
-                    ParentChild { parent, child }.into()
-                } else {
-                    parent.into()
-                }
-            }
+            PlayerState::Idling(state) => Execute::<Aim>::execute(state, tick).into(),
+            PlayerState::Charging(state) => Execute::<Aim>::execute(state, tick).into(),
             _ => state,
         }
     }
 
-    pub(super) fn try_start_contract(state: PlayerState) -> PlayerState {
+    pub(super) fn try_dash(state: PlayerState, tick: &Tick) -> PlayerState {
         match state {
-            PlayerState::ParentChildIdlingExtending(state) => {
-                let ParentChild { parent, child } = state;
-                match hook::action::start_contract(child) {
-                    Ok(contracting) => ParentChild {
-                        parent,
-                        child: contracting,
-                    }
-                    .into(),
-                    Err(extending) => ParentChild {
-                        parent,
-                        child: extending,
-                    }
-                    .into(),
-                }
-            }
+            PlayerState::Idling(state) => Execute::<Dash>::execute(state, tick).into(),
+            _ => state,
+        }
+    }
+    pub(super) fn try_burst(state: PlayerState, tick: &Tick) -> PlayerState {
+        match state {
+            PlayerState::Dashing(state) => Execute::<Burst>::execute(state, tick).into(),
             _ => state,
         }
     }
 
+    /// Actions of a thrown hook. They run after the player's, with the tail already on the player
+    pub(super) enum HookActionType {
+        Retract,
+        SnapBack,
+        Catch,
+        Graple,
+        Extend,
+        StartContract,
+        Contract,
+        Reel,
+        Snap,
+    }
+
+    pub(super) fn execute_hook_actions(
+        actions: Vec<HookActionType>,
+        executor: ThrownHook,
+        tick: &Tick,
+    ) -> Result<ThrownHook, HookEnd> {
+        let mut thrown = executor;
+        for action in actions {
+            thrown = match action {
+                HookActionType::Retract => try_retract(thrown, tick),
+                HookActionType::SnapBack => try_snap_back(thrown, tick),
+                HookActionType::Catch => try_catch(thrown, tick),
+                HookActionType::Graple => try_graple(thrown, tick),
+                HookActionType::Extend => try_extend(thrown, tick),
+                HookActionType::StartContract => try_start_contract(thrown),
+                HookActionType::Contract => try_contract(thrown, tick)?,
+                HookActionType::Reel => try_reel(thrown, tick)?,
+                HookActionType::Snap => try_snap(thrown, tick)?,
+            };
+        }
+        Ok(thrown)
+    }
+    pub(super) fn try_retract(thrown: ThrownHook, tick: &Tick) -> ThrownHook {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Extending(state) => Execute::<Retract>::execute(state, tick).into(),
+            state => state,
+        };
+        ThrownHook { hand, state }
+    }
+    pub(super) fn try_snap_back(thrown: ThrownHook, tick: &Tick) -> ThrownHook {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Extending(state) => Execute::<SnapBack>::execute(state, tick).into(),
+            state => state,
+        };
+        ThrownHook { hand, state }
+    }
+    pub(super) fn try_catch(thrown: ThrownHook, tick: &Tick) -> ThrownHook {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Extending(state) => Execute::<Catch>::execute(state, tick).into(),
+            state => state,
+        };
+        ThrownHook { hand, state }
+    }
+    pub(super) fn try_graple(thrown: ThrownHook, tick: &Tick) -> ThrownHook {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Extending(state) => match (hand, Execute::<Graple>::execute(state, tick)) {
+                // Off-hand hooks cannot carry the player, so one that grapples lets go at once and turns back
+                (Hand::Off, Ok(anchored)) => HookState::Contracting(anchored.let_go()),
+                (Hand::Main, Ok(anchored)) => HookState::Anchored(anchored),
+                (_, Err(state)) => HookState::Extending(state),
+            },
+            state => state,
+        };
+        ThrownHook { hand, state }
+    }
+    pub(super) fn try_extend(thrown: ThrownHook, tick: &Tick) -> ThrownHook {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Extending(state) => HookState::Extending(hook::action::extend(state, tick)),
+            state => state,
+        };
+        ThrownHook { hand, state }
+    }
+    pub(super) fn try_start_contract(thrown: ThrownHook) -> ThrownHook {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Extending(state) => hook::action::start_contract(state).into(),
+            state => state,
+        };
+        ThrownHook { hand, state }
+    }
+    pub(super) fn try_contract(thrown: ThrownHook, tick: &Tick) -> Result<ThrownHook, HookEnd> {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Contracting(state) => {
+                HookState::Contracting(hook::action::contract(state, tick).ok_or(HookEnd::Returned(hand))?)
+            }
+            state => state,
+        };
+        Ok(ThrownHook { hand, state })
+    }
+    pub(super) fn try_reel(thrown: ThrownHook, tick: &Tick) -> Result<ThrownHook, HookEnd> {
+        let ThrownHook { hand, state } = thrown;
+        let state = match state {
+            HookState::Anchored(state) => {
+                HookState::Anchored(hook::action::reel(state, tick.dt).map_err(HookEnd::Landed)?)
+            }
+            state => state,
+        };
+        Ok(ThrownHook { hand, state })
+    }
+
+    /// The player lets go of a snapped chain, and the head is left in the world
+    pub(super) fn try_snap(thrown: ThrownHook, tick: &Tick) -> Result<ThrownHook, HookEnd> {
+        let ThrownHook { hand, state } = thrown;
+        let snapped = match state {
+            HookState::Extending(state) => hook::action::snap(state).map_err(HookState::Extending),
+            HookState::Contracting(state) => hook::action::snap(state).map_err(HookState::Contracting),
+            HookState::Anchored(state) => hook::action::snap(state).map_err(HookState::Anchored),
+        };
+        match snapped {
+            Ok(dropped) => {
+                tick.spawn(StateMachineEnum::DroppedHook(dropped));
+                Err(HookEnd::Snapped(hand))
+            }
+            Err(state) => Ok(ThrownHook { hand, state }),
+        }
+    }
+    /// Accelerates and turns toward the movement input. Fails without input, in which case the player coasts to a halt
     pub struct Run {
         pub position: Position,
-        pub max_speed: Magnitude,
+        pub direction: Direction,
+        pub current_speed: Magnitude,
+        pub physics: Physics,
     }
     pub struct OkRun {
         pub position: Position,
@@ -414,20 +920,25 @@ mod action {
         pub current_speed: Magnitude,
     }
     pub struct ErrRun {
+        pub position: Position,
         pub current_speed: Magnitude,
     }
     impl Action for Run {
         type OkOutput = OkRun;
         type ErrOutput = ErrRun;
-        fn execute<T: Execute<Self>>(self, state: T) -> Result<T::OkState, T::ErrState> {
-            let direction = get_player_move();
-            if direction.is_zero() {
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            let Run { position, direction, current_speed, physics } = self;
+            let input = tick.input.movement;
+            if input.is_zero() {
+                let current_speed = physics.decelerate(current_speed, tick.dt);
                 Err(state.move_to_err_state(ErrRun {
-                    current_speed: Magnitude::zero(),
+                    position: physics.calculate_new_position(position, current_speed, direction, tick.dt),
+                    current_speed,
                 }))
             } else {
-                let current_speed = self.max_speed;
-                let position = Physics::calculate_new_position_from_speed(self.position, current_speed, direction);
+                let direction = physics.turn(direction, input, current_speed, tick.dt);
+                let current_speed = physics.accelerate(current_speed, tick.dt);
+                let position = physics.calculate_new_position(position, current_speed, direction, tick.dt);
                 Ok(state.move_to_ok_state(OkRun {
                     position,
                     direction,
@@ -437,12 +948,153 @@ mod action {
         }
     }
 
-    pub struct Shoot;
+    /// Faces the cursor. Fails when the player does not aim with the mouse, in which case it faces where it moves
+    pub struct Aim {
+        pub position: Position,
+        pub facing: Direction,
+    }
+    impl Action for Aim {
+        type OkOutput = Direction;
+        type ErrOutput = ();
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            match tick.input.aim {
+                Some(cursor) => Ok(state.move_to_ok_state(Direction::a_to_b(self.position, cursor).or(self.facing))),
+                None => Err(state.move_to_err_state(())),
+            }
+        }
+    }
+
+    pub struct Dash {
+        pub cooldown: f32,
+    }
+    impl Action for Dash {
+        type OkOutput = ();
+        type ErrOutput = ();
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            if tick.input.dash.pressed && self.cooldown <= 0.0 {
+                Ok(state.move_to_ok_state(()))
+            } else {
+                Err(state.move_to_err_state(()))
+            }
+        }
+    }
+
+    /// Movement during a dash. Fails when the dash has run its course
+    pub struct Burst {
+        pub position: Position,
+        pub direction: Direction,
+        pub remaining: f32,
+        pub invulnerable: f32,
+    }
+    pub struct OkBurst {
+        pub position: Position,
+        pub remaining: f32,
+        pub invulnerable: f32,
+    }
+    impl Action for Burst {
+        type OkOutput = OkBurst;
+        type ErrOutput = ();
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            if self.remaining <= 0.0 {
+                Err(state.move_to_err_state(()))
+            } else {
+                let dt = tick.dt.min(self.remaining);
+                let position =
+                    Physics::calculate_new_position_from_speed(self.position, DASH_SPEED, self.direction, dt);
+                Ok(state.move_to_ok_state(OkBurst {
+                    position,
+                    remaining: self.remaining - dt,
+                    invulnerable: (self.invulnerable - dt).max(0.0),
+                }))
+            }
+        }
+    }
+
+    /// Succeeds when the hook head touched an obstacle in the contacts of the last step
+    pub struct Graple {
+        pub touching: bool,
+    }
+    impl Action for Graple {
+        type OkOutput = ();
+        type ErrOutput = ();
+        fn execute<T: Execute<Self>>(self, state: T, _: &Tick) -> Result<T::OkState, T::ErrState> {
+            if self.touching {
+                Ok(state.move_to_ok_state(()))
+            } else {
+                Err(state.move_to_err_state(()))
+            }
+        }
+    }
+
+    /// Succeeds when the hook head touched an item in the contacts of the last step
+    pub struct Catch {
+        pub touching: bool,
+    }
+    impl Action for Catch {
+        type OkOutput = ();
+        type ErrOutput = ();
+        fn execute<T: Execute<Self>>(self, state: T, _: &Tick) -> Result<T::OkState, T::ErrState> {
+            if self.touching {
+                Ok(state.move_to_ok_state(()))
+            } else {
+                Err(state.move_to_err_state(()))
+            }
+        }
+    }
+
+    /// Succeeds when the player asks for an extending hook back
+    pub struct Retract;
+    impl Action for Retract {
+        type OkOutput = ();
+        type ErrOutput = ();
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            if tick.input.retract.pressed {
+                Ok(state.move_to_ok_state(()))
+            } else {
+                Err(state.move_to_err_state(()))
+            }
+        }
+    }
+
+    /// Succeeds when shoot is pressed again while the hook is still extending
+    pub struct SnapBack;
+    impl Action for SnapBack {
+        type OkOutput = ();
+        type ErrOutput = ();
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            if tick.input.shoot.pressed {
+                Ok(state.move_to_ok_state(()))
+            } else {
+                Err(state.move_to_err_state(()))
+            }
+        }
+    }
+
+    /// Fires the hook once shoot is let go. Until then the charge builds up
+    pub struct Release {
+        pub charge: f32,
+    }
+    impl Action for Release {
+        type OkOutput = ();
+        type ErrOutput = f32;
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            if tick.input.shoot.held {
+                Err(state.move_to_err_state((self.charge + tick.dt / CHARGE_DURATION).min(1.0)))
+            } else {
+                Ok(state.move_to_ok_state(()))
+            }
+        }
+    }
+
+    /// Starts charging a throw. Fails while the main hook is out
+    pub struct Shoot {
+        pub hook_in_hand: bool,
+    }
     impl Action for Shoot {
         type OkOutput = ();
         type ErrOutput = ();
-        fn execute<T: Execute<Self>>(self, state: T) -> Result<T::OkState, T::ErrState> {
-            if is_shooting() {
+        fn execute<T: Execute<Self>>(self, state: T, tick: &Tick) -> Result<T::OkState, T::ErrState> {
+            if tick.input.shoot.pressed && self.hook_in_hand {
                 Ok(state.move_to_ok_state(()))
             } else {
                 Err(state.move_to_err_state(()))
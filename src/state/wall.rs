@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use macroquad::math::Vec2;
+
+use crate::{
+    collision::{self, CollisionBox, Contact, Part, Triangles},
+    draw::{
+        Draw, Drawable,
+        colors::DARKGRAY,
+        graphics::{Rectangle, Shape},
+    },
+    model::*,
+    state::{StateMachine, StateObject, Tick},
+    util::name_of_type,
+};
+
+/// Solid, static obstacle. `position` is the center
+#[derive(Debug)]
+pub struct Wall {
+    position: Position,
+    width: f32,
+    height: f32,
+}
+impl Wall {
+    pub fn new(position: Position, width: f32, height: f32) -> Self {
+        Wall { position, width, height }
+    }
+    pub fn bounds(&self) -> CollisionBox {
+        let half_size = Vec2::new(self.width, self.height) / 2.0;
+        CollisionBox {
+            lower: self.position.value() - half_size,
+            upper: self.position.value() + half_size,
+        }
+    }
+}
+impl StateMachine for Wall {
+    fn state_object(&self) -> Vec<StateObject> {
+        vec![StateObject {
+            position: self.position,
+            direction: RIGHT,
+        }]
+    }
+
+    fn update(self, _: &Tick) -> Self {
+        self
+    }
+}
+impl Draw for Wall {
+    fn drawable(&self) -> Vec<Drawable> {
+        vec![Drawable {
+            state: self.state_object()[0],
+            shape: Shape::Rectangle(Rectangle {
+                height: self.height,
+                width: self.width,
+                color: DARKGRAY,
+            }),
+        }]
+    }
+}
+impl collision::Collision for Wall {
+    fn collision_shape(&self) -> Vec<(Part, Triangles)> {
+        vec![(Part::Wall, self.bounds().into())]
+    }
+
+    /// Walls do not give way
+    fn collision_detected(self, _: &Contact) -> Self {
+        self
+    }
+}
+impl Display for Wall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}x{}", name_of_type(self), self.position, self.width, self.height)
+    }
+}
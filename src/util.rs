@@ -14,7 +14,9 @@ where
     stack: Vec<T>,
     head: A,
     tail: B,
-    function: fn(&mut T, &T),
+    /// Passed on to `function`
+    spacing: f32,
+    function: fn(&mut T, &T, f32),
 }
 
 impl<T, A, B> Stack<T, A, B>
@@ -23,8 +25,9 @@ where
     A: AsRef<T> + AsMut<T>,
     B: AsRef<T>,
 {
-    pub fn new(head: A, tail: B, function: fn(&mut T, &T)) -> Self {
-        Stack { stack: vec![], head, tail, function }
+    /// `function` keeps an item `spacing` away from the one before it
+    pub fn new(head: A, tail: B, spacing: f32, function: fn(&mut T, &T, f32)) -> Self {
+        Stack { stack: vec![], head, tail, spacing, function }
     }
     pub fn head(&self) -> &A {
         &self.head
@@ -79,32 +82,35 @@ where
     pub fn iter(&self) -> slice::Iter<'_, T> {
         self.stack.iter()
     }
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.stack.iter_mut()
+    }
     pub fn into_iter(self) -> <std::vec::Vec<T> as std::iter::IntoIterator>::IntoIter {
         self.stack.into_iter()
     }
 
     pub fn fold_into_self(self) -> Self {
-        let Self { mut stack, function, .. } = self;
+        let Self { mut stack, spacing, function, .. } = self;
         stack.iter_mut().fold(Stack { stack: vec![], ..self }, |mut acc, mut current| {
-            function(current, acc.last());
+            function(current, acc.last(), spacing);
             acc.push(current.clone());
             acc
         })
     }
     pub fn rfold_into_self(self, init: &[T]) -> Self {
-        let Self { mut stack, head, function, .. } = self;
+        let Self { mut stack, head, spacing, function, .. } = self;
 
         let mut stack = stack.iter_mut().rfold(
             Stack { head, stack: init.into(), ..self },
             |mut acc, mut current| {
-                function(current, acc.last());
+                function(current, acc.last(), spacing);
                 acc.push(current.clone());
                 acc
             },
         );
 
         let last = stack.last().clone();
-        function(stack.head.as_mut(), &last);
+        function(stack.head.as_mut(), &last, spacing);
         stack.stack.reverse();
         stack
     }